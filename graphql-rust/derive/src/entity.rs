@@ -0,0 +1,298 @@
+use crate::util::{camel_case, column_const, is_graphql_skipped, named_fields, sakila_attributes};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, DeriveInput, Error, Expr, Field, Ident, Lit, LitStr, Path, Result, Token, Type,
+};
+
+enum Column {
+    // a column, for which a constant is generated
+    Const(Ident, LitStr),
+    // a column named by an existing constant
+    Path(Path),
+}
+
+impl ToTokens for Column {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Column::Const(name, _) => quote!(Self::#name).to_tokens(tokens),
+            Column::Path(path) => path.to_tokens(tokens),
+        }
+    }
+}
+
+struct EntityField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    column: Column,
+    primary_key: bool,
+    computed: bool,
+    prefetched: bool,
+    skip: bool,
+    graphql_skip: bool,
+    resolves: Vec<LitStr>,
+    json: Option<Path>,
+}
+
+impl<'a> EntityField<'a> {
+    fn parse(field: &'a Field) -> Result<Self> {
+        let ident = field.ident.as_ref().expect("named field");
+        let name = ident.to_string();
+
+        let mut result = Self {
+            ident,
+            ty: &field.ty,
+            column: Column::Const(
+                format_ident!("{}", column_const(&name)),
+                LitStr::new(&name, ident.span()),
+            ),
+            primary_key: false,
+            computed: false,
+            prefetched: false,
+            skip: false,
+            graphql_skip: is_graphql_skipped(field),
+            resolves: vec![],
+            json: None,
+        };
+
+        for attr in sakila_attributes(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("primary_key") {
+                    result.primary_key = true;
+                } else if meta.path.is_ident("computed") {
+                    result.computed = true;
+                } else if meta.path.is_ident("prefetched") {
+                    result.prefetched = true;
+                } else if meta.path.is_ident("skip") {
+                    result.skip = true;
+                } else if meta.path.is_ident("column") {
+                    result.column = match meta.value()?.parse::<Expr>()? {
+                        Expr::Lit(lit) => match lit.lit {
+                            Lit::Str(column) => {
+                                Column::Const(format_ident!("{}", column_const(&name)), column)
+                            }
+                            lit => return Err(Error::new_spanned(lit, "expected a string")),
+                        },
+                        Expr::Path(path) => Column::Path(path.path),
+                        expr => {
+                            return Err(Error::new_spanned(expr, "expected a string or a path"))
+                        }
+                    };
+                } else if meta.path.is_ident("json") {
+                    result.json = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("resolves") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let fields = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                    result.resolves.extend(fields);
+                } else {
+                    return Err(meta.error("unsupported sakila attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    fn is_column(&self) -> bool {
+        !self.prefetched && !self.skip
+    }
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = named_fields(&input)?
+        .map(EntityField::parse)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut primary_keys = fields.iter().filter(|f| f.primary_key);
+    let (Some(pk), None) = (primary_keys.next(), primary_keys.next()) else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "expected exactly one field with #[sakila(primary_key)]",
+        ));
+    };
+
+    let columns = fields.iter().filter(|f| f.is_column()).collect::<Vec<_>>();
+
+    let consts = columns.iter().filter_map(|f| match &f.column {
+        Column::Const(name, value) => Some(quote!(pub const #name: &'static str = #value;)),
+        Column::Path(_) => None,
+    });
+
+    let mapped = columns
+        .iter()
+        .filter(|f| !f.computed && !f.graphql_skip)
+        .map(|f| {
+            let field = camel_case(&f.ident.to_string());
+            let column = &f.column;
+            quote!(map.insert(#field, #column);)
+        });
+    let resolved = columns.iter().flat_map(|f| {
+        let column = &f.column;
+        f.resolves
+            .iter()
+            .map(move |field| quote!(map.insert(#field, #column);))
+    });
+
+    let computed = columns
+        .iter()
+        .filter(|f| f.computed)
+        .map(|f| camel_case(&f.ident.to_string()))
+        .collect::<Vec<_>>();
+    let computed = match computed.is_empty() {
+        true => quote!(),
+        false => quote!(const COMPUTED_FIELDS: &'static [&'static str] = &[#(#computed),*];),
+    };
+
+    let pk_ident = pk.ident;
+    let pk_ty = pk.ty;
+    let pk_column = &pk.column;
+
+    let column_list = columns.iter().filter(|f| !f.computed).map(|f| &f.column);
+    let computed_columns = columns
+        .iter()
+        .filter(|f| f.computed)
+        .map(|f| &f.column)
+        .collect::<Vec<_>>();
+    // the computed columns are only fetched by the queries which compute them
+    let from_row = match computed_columns.is_empty() {
+        true => quote!(),
+        false => quote! {
+            fn from_row(row: &::tokio_postgres::Row) -> ::std::result::Result<Self, ::anyhow::Error>
+            where
+                Self: Sized,
+            {
+                let mut columns = Self::COLUMNS.to_vec();
+                for computed in [#(#computed_columns),*] {
+                    if row.columns().iter().any(|c| c.name() == computed) {
+                        columns.push(computed);
+                    }
+                }
+
+                Self::from_columns(row, &columns)
+            }
+        },
+    };
+    let row_arms = columns.iter().map(|f| {
+        let ident = f.ident;
+        let column = &f.column;
+        quote!(#column => result.#ident = row.try_get(#column)?,)
+    });
+
+    let json_computed = match computed_columns.is_empty() {
+        true => quote!(),
+        false => quote! {
+            let mut columns = columns.to_vec();
+            for computed in [#(#computed_columns),*] {
+                if object.contains_key(computed) {
+                    columns.push(computed);
+                }
+            }
+            let columns = columns.as_slice();
+        },
+    };
+    // the prefetched entities are only fetched along with the selected relations
+    let prefetched = fields.iter().filter(|f| f.prefetched).map(|f| {
+        let ident = f.ident;
+        quote! {
+            if let Some(value) = object.remove(crate::query::PREFETCHED_KEY) {
+                result.#ident = crate::graphql::core::prefetch::Prefetched::from_json(value)?;
+            }
+        }
+    });
+    let json_arms = columns.iter().map(|f| {
+        let ident = f.ident;
+        let column = &f.column;
+        let json = match &f.json {
+            Some(path) => quote!(#path),
+            None => quote!(crate::graphql::core::prefetch::json_value),
+        };
+        quote!(#column => result.#ident = #json(value)?,)
+    });
+
+    Ok(quote! {
+        impl #name {
+            #(#consts)*
+        }
+
+        impl crate::graphql::core::filter::TypeInfo for #name {
+            const PRIMARY_KEY: &'static str = #pk_column;
+            #computed
+
+            const QUERY_FIELD_TO_DB_COLUMN_MAP: ::std::sync::LazyLock<
+                ::rustc_hash::FxHashMap<&'static str, &'static str>,
+            > = ::std::sync::LazyLock::new(|| {
+                let mut map = ::rustc_hash::FxHashMap::default();
+                #(#mapped)*
+                #(#resolved)*
+                map
+            });
+        }
+
+        impl crate::graphql::core::loader::WithId<#pk_ty> for #name {
+            fn id(&self) -> &#pk_ty {
+                &self.#pk_ident
+            }
+        }
+
+        impl crate::graphql::core::loader::FromRow for #name {
+            const COLUMNS: &'static [&'static str] = &[#(#column_list),*];
+
+            fn from_columns(
+                row: &::tokio_postgres::Row,
+                columns: &[&str],
+            ) -> ::std::result::Result<Self, ::anyhow::Error>
+            where
+                Self: Sized,
+            {
+                let mut result = Self::default();
+                for &column in columns {
+                    match column {
+                        #(#row_arms)*
+                        column => ::anyhow::bail!(
+                            "the column '{}' is not mapped to a field of '{}'",
+                            column,
+                            ::std::any::type_name::<Self>()
+                        ),
+                    }
+                }
+
+                Ok(result)
+            }
+
+            #from_row
+        }
+
+        impl crate::graphql::core::prefetch::FromJson for #name {
+            fn from_json(
+                mut object: crate::graphql::core::prefetch::JsonObject,
+                columns: &[&str],
+            ) -> ::std::result::Result<Self, ::anyhow::Error>
+            where
+                Self: Sized,
+            {
+                let mut result = Self::default();
+                #(#prefetched)*
+
+                #json_computed
+                for &column in columns {
+                    let value = crate::graphql::core::prefetch::json_column(&mut object, column)?;
+                    match column {
+                        #(#json_arms)*
+                        column => ::anyhow::bail!(
+                            "the column '{}' is not mapped to a field of '{}'",
+                            column,
+                            ::std::any::type_name::<Self>()
+                        ),
+                    }
+                }
+
+                Ok(result)
+            }
+        }
+    })
+}
@@ -0,0 +1,496 @@
+use crate::util::{column_const, named_fields, sakila_attributes};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{DeriveInput, Error, Expr, Field, Ident, LitStr, Path, Result};
+
+// The operations of the value filters, by the suffix of the field name
+const OPERATIONS: &[(&str, &str)] = &[
+    ("not_eq", "neq"),
+    ("not_in", "is_not_in"),
+    ("i_contains", "i_contains"),
+    ("starts_with", "starts_with"),
+    ("ends_with", "ends_with"),
+    ("contains", "contains"),
+    ("ilike", "ilike"),
+    ("regex", "regex"),
+    ("eq", "eq"),
+    ("in", "is_in"),
+    ("gte", "gte"),
+    ("gt", "gt"),
+    ("lte", "lte"),
+    ("lt", "lt"),
+];
+
+// The operations, which are only available through `#[sakila(op = "...")]`
+const EXPLICIT_OPERATIONS: &[&str] = &["array_contains", "null_check"];
+
+// The comparisons of the number of related entities, by the suffix of the field name
+const COUNT_OPERATIONS: &[(&str, &str)] = &[
+    ("eq", "count_eq"),
+    ("gte", "count_gte"),
+    ("gt", "count_gt"),
+    ("lte", "count_lte"),
+    ("lt", "count_lt"),
+];
+
+struct JoinTable {
+    name: LitStr,
+    table: Expr,
+    table_parent_col: Expr,
+    table_child_col: Expr,
+    parent_col: Expr,
+    child_col: Expr,
+}
+
+impl JoinTable {
+    fn parse(meta: ParseNestedMeta) -> Result<Self> {
+        let mut name = None;
+        let mut table = None;
+        let mut table_parent_col = None;
+        let mut table_child_col = None;
+        let mut parent_col = None;
+        let mut child_col = None;
+
+        meta.parse_nested_meta(|meta| {
+            let value = meta.value()?;
+            if meta.path.is_ident("name") {
+                name = Some(value.parse()?);
+            } else if meta.path.is_ident("table") {
+                table = Some(value.parse()?);
+            } else if meta.path.is_ident("table_parent_col") {
+                table_parent_col = Some(value.parse()?);
+            } else if meta.path.is_ident("table_child_col") {
+                table_child_col = Some(value.parse()?);
+            } else if meta.path.is_ident("parent_col") {
+                parent_col = Some(value.parse()?);
+            } else if meta.path.is_ident("child_col") {
+                child_col = Some(value.parse()?);
+            } else {
+                return Err(meta.error("unsupported join table attribute"));
+            }
+
+            Ok(())
+        })?;
+
+        let missing = |name: &str| meta.error(format!("missing join table attribute `{}`", name));
+        Ok(Self {
+            name: name.ok_or_else(|| missing("name"))?,
+            table: table.ok_or_else(|| missing("table"))?,
+            table_parent_col: table_parent_col.ok_or_else(|| missing("table_parent_col"))?,
+            table_child_col: table_child_col.ok_or_else(|| missing("table_child_col"))?,
+            parent_col: parent_col.ok_or_else(|| missing("parent_col"))?,
+            child_col: child_col.ok_or_else(|| missing("child_col"))?,
+        })
+    }
+
+    // The arguments following the filter of the related entities
+    fn arguments(&self) -> TokenStream {
+        let Self {
+            table,
+            table_parent_col,
+            table_child_col,
+            parent_col,
+            child_col,
+            ..
+        } = self;
+
+        quote!(#table, #table_parent_col, #table_child_col, #parent_col, #child_col)
+    }
+}
+
+enum FilterKind {
+    And,
+    Or,
+    Not,
+    Skip,
+    Value {
+        column: Option<Expr>,
+        operation: Option<LitStr>,
+    },
+    TextSearch(Expr),
+    JoinColumn {
+        parent_col: Expr,
+        child_col: Expr,
+    },
+    JoinTable {
+        join: LitStr,
+        function: &'static str,
+    },
+    CountFilter(LitStr),
+    Count {
+        join: LitStr,
+        operation: Option<LitStr>,
+    },
+}
+
+struct FilterField<'a> {
+    ident: &'a Ident,
+    kind: FilterKind,
+}
+
+impl<'a> FilterField<'a> {
+    fn parse(field: &'a Field) -> Result<Self> {
+        let ident = field.ident.as_ref().expect("named field");
+
+        let mut kind = None;
+        let mut column = None;
+        let mut operation = None;
+        let mut quantifier = "join_table";
+
+        let mut set = |k: FilterKind, meta: &ParseNestedMeta| match kind.replace(k) {
+            Some(_) => Err(meta.error("conflicting sakila attributes")),
+            None => Ok(()),
+        };
+
+        for attr in sakila_attributes(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("and") {
+                    set(FilterKind::And, &meta)?;
+                } else if meta.path.is_ident("or") {
+                    set(FilterKind::Or, &meta)?;
+                } else if meta.path.is_ident("not") {
+                    set(FilterKind::Not, &meta)?;
+                } else if meta.path.is_ident("skip") {
+                    set(FilterKind::Skip, &meta)?;
+                } else if meta.path.is_ident("column") {
+                    column = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("op") {
+                    operation = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("text_search") {
+                    set(FilterKind::TextSearch(meta.value()?.parse()?), &meta)?;
+                } else if meta.path.is_ident("join_column") {
+                    let mut parent_col = None;
+                    let mut child_col = None;
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("parent_col") {
+                            parent_col = Some(meta.value()?.parse()?);
+                        } else if meta.path.is_ident("child_col") {
+                            child_col = Some(meta.value()?.parse()?);
+                        } else {
+                            return Err(meta.error("unsupported join column attribute"));
+                        }
+
+                        Ok(())
+                    })?;
+
+                    let (Some(parent_col), Some(child_col)) = (parent_col, child_col) else {
+                        return Err(meta.error("expected `parent_col` and `child_col`"));
+                    };
+                    set(
+                        FilterKind::JoinColumn {
+                            parent_col,
+                            child_col,
+                        },
+                        &meta,
+                    )?;
+                } else if meta.path.is_ident("join_table") {
+                    let join = meta.value()?.parse()?;
+                    set(
+                        FilterKind::JoinTable {
+                            join,
+                            function: "join_table",
+                        },
+                        &meta,
+                    )?;
+                } else if meta.path.is_ident("every") {
+                    quantifier = "join_table_every";
+                } else if meta.path.is_ident("none") {
+                    quantifier = "join_table_none";
+                } else if meta.path.is_ident("count_filter") {
+                    set(FilterKind::CountFilter(meta.value()?.parse()?), &meta)?;
+                } else if meta.path.is_ident("count") {
+                    let join = meta.value()?.parse()?;
+                    set(
+                        FilterKind::Count {
+                            join,
+                            operation: None,
+                        },
+                        &meta,
+                    )?;
+                } else {
+                    return Err(meta.error("unsupported sakila attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let kind = match kind {
+            None => FilterKind::Value { column, operation },
+            Some(FilterKind::JoinTable { join, .. }) => FilterKind::JoinTable {
+                join,
+                function: quantifier,
+            },
+            Some(FilterKind::Count { join, .. }) if column.is_none() => {
+                FilterKind::Count { join, operation }
+            }
+            Some(_) if column.is_some() || operation.is_some() => {
+                return Err(Error::new_spanned(
+                    field,
+                    "`column` and `op` are only supported by value filters",
+                ));
+            }
+            Some(kind) => kind,
+        };
+
+        Ok(Self { ident, kind })
+    }
+}
+
+// Splits the name of a filter field into its column and operation
+fn split_operation<'n>(
+    name: &'n str,
+    operations: &[(&str, &'static str)],
+) -> Option<(&'n str, &'static str)> {
+    operations.iter().find_map(|&(suffix, op)| {
+        name.strip_suffix(suffix)
+            .and_then(|column| column.strip_suffix('_'))
+            .filter(|column| !column.is_empty())
+            .map(|column| (column, op))
+    })
+}
+
+fn known_operation(op: &str, operations: &[(&str, &str)]) -> bool {
+    operations.iter().any(|(_, o)| *o == op)
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let mut table: Option<LitStr> = None;
+    let mut entity: Option<Path> = None;
+    let mut joins: Vec<JoinTable> = vec![];
+    for attr in sakila_attributes(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("entity") {
+                entity = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("join_table") {
+                joins.push(JoinTable::parse(meta)?);
+            } else {
+                return Err(meta.error("unsupported sakila attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let Some(table) = table else {
+        return Err(Error::new_spanned(
+            name,
+            "expected #[sakila(table = \"...\")]",
+        ));
+    };
+
+    let fields = named_fields(&input)?
+        .map(FilterField::parse)
+        .collect::<Result<Vec<_>>>()?;
+
+    let find_join = |join: &LitStr| {
+        joins
+            .iter()
+            .find(|j| j.name.value() == join.value())
+            .ok_or_else(|| Error::new_spanned(join, "unknown join table"))
+    };
+
+    let nested =
+        |kind: fn(&FilterKind) -> bool| fields.iter().find(|f| kind(&f.kind)).map(|f| f.ident);
+    let and_filters = match nested(|k| matches!(k, FilterKind::And)) {
+        Some(field) => quote!(self.#field.as_ref().map(|x| x.as_slice())),
+        None => quote!(None),
+    };
+    let or_filters = match nested(|k| matches!(k, FilterKind::Or)) {
+        Some(field) => quote!(self.#field.as_ref().map(|x| x.as_slice())),
+        None => quote!(None),
+    };
+    let not_filter = match nested(|k| matches!(k, FilterKind::Not)) {
+        Some(field) => quote!(self.#field.as_deref()),
+        None => quote!(None),
+    };
+
+    let mut searches = vec![];
+    let mut values = vec![];
+    let mut relations = vec![];
+    let mut counters = vec![];
+    let mut text_search = quote!();
+
+    for field in &fields {
+        let ident = field.ident;
+        match &field.kind {
+            FilterKind::And | FilterKind::Or | FilterKind::Not | FilterKind::Skip => {}
+            FilterKind::TextSearch(column) => {
+                searches.push(quote! {
+                    crate::graphql::core::filter::text_search(&mut g, #column, &self.#ident);
+                });
+                text_search = quote! {
+                    fn text_search(&self) -> Option<crate::query::TextSearch<'_>> {
+                        self.#ident.as_ref().map(|query| crate::query::TextSearch {
+                            column: #column,
+                            query,
+                        })
+                    }
+                };
+            }
+            FilterKind::Value { column, operation } => {
+                let field_name = ident.to_string();
+                let inferred = split_operation(&field_name, OPERATIONS);
+
+                let operation = match (operation, inferred) {
+                    (Some(op), _) => {
+                        let known = known_operation(&op.value(), OPERATIONS)
+                            || EXPLICIT_OPERATIONS.contains(&op.value().as_str());
+                        if !known {
+                            return Err(Error::new_spanned(op, "unknown operation"));
+                        }
+                        format_ident!("{}", op.value())
+                    }
+                    (None, Some((_, op))) => format_ident!("{}", op),
+                    (None, None) => {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "cannot infer the operation of the filter",
+                        ))
+                    }
+                };
+
+                let column = match (column, inferred.map(|(c, _)| c), &entity) {
+                    (Some(column), _, _) => quote!(#column),
+                    (None, Some(column), Some(entity)) => {
+                        let column = format_ident!("{}", column_const(column));
+                        quote!(#entity::#column)
+                    }
+                    _ => {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "cannot infer the column of the filter",
+                        ))
+                    }
+                };
+
+                values.push(quote! {
+                    crate::graphql::core::filter::#operation(&mut g, #column, &self.#ident);
+                });
+            }
+            FilterKind::JoinColumn {
+                parent_col,
+                child_col,
+            } => {
+                relations.push(quote! {
+                    crate::graphql::core::filter::join_column(&mut g, &self.#ident, #parent_col, #child_col);
+                });
+            }
+            FilterKind::JoinTable { join, function } => {
+                let arguments = find_join(join)?.arguments();
+                let function = format_ident!("{}", function);
+                relations.push(quote! {
+                    crate::graphql::core::filter::#function(&mut g, &self.#ident, #arguments);
+                });
+            }
+            FilterKind::CountFilter(join) => {
+                let arguments = find_join(join)?.arguments();
+                let counter = format_ident!("{}", join.value());
+                counters.push(quote! {
+                    let #counter = crate::graphql::core::filter::join_table_counter(&self.#ident, #arguments);
+                });
+
+                for count in &fields {
+                    let FilterKind::Count {
+                        join: count_join,
+                        operation,
+                    } = &count.kind
+                    else {
+                        continue;
+                    };
+                    if count_join.value() != join.value() {
+                        continue;
+                    }
+
+                    let count_ident = count.ident;
+                    let function = match operation {
+                        Some(op) if known_operation(&op.value(), COUNT_OPERATIONS) => {
+                            format_ident!("{}", op.value())
+                        }
+                        Some(op) => return Err(Error::new_spanned(op, "unknown count operation")),
+                        None => match split_operation(&count_ident.to_string(), COUNT_OPERATIONS) {
+                            Some((_, op)) => format_ident!("{}", op),
+                            None => {
+                                return Err(Error::new_spanned(
+                                    count_ident,
+                                    "cannot infer the operation of the count filter",
+                                ))
+                            }
+                        },
+                    };
+
+                    counters.push(quote! {
+                        crate::graphql::core::filter::#function(&mut g, &#counter, &self.#count_ident);
+                    });
+                }
+            }
+            FilterKind::Count { join, .. } => {
+                let has_filter = fields.iter().any(
+                    |f| matches!(&f.kind, FilterKind::CountFilter(j) if j.value() == join.value()),
+                );
+                if !has_filter {
+                    return Err(Error::new_spanned(
+                        ident,
+                        "the count requires a field with #[sakila(count_filter = \"...\")]",
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl crate::graphql::core::filter::InputFilter for #name {
+            const TABLE_NAME: &'static str = #table;
+
+            fn or_filters(&self) -> Option<&[Self]>
+            where
+                Self: Sized,
+            {
+                #or_filters
+            }
+
+            fn and_filters(&self) -> Option<&[Self]>
+            where
+                Self: Sized,
+            {
+                #and_filters
+            }
+
+            fn not_filter(&self) -> Option<&Self>
+            where
+                Self: Sized,
+            {
+                #not_filter
+            }
+
+            fn collect_into<'f>(&'f self, collector: &mut crate::query::FilterGroup<'f>) {
+                let mut g = match collector.combinator() == crate::query::Combinator::And {
+                    true => crate::util::MaybeOwned::Borrowed(collector),
+                    false => crate::util::MaybeOwned::Owned(crate::query::FilterGroup::new(
+                        crate::query::Combinator::And,
+                    )),
+                };
+
+                crate::graphql::core::filter::and_filters(&mut g, self);
+                crate::graphql::core::filter::or_filters(&mut g, self);
+                crate::graphql::core::filter::not_filter(&mut g, self);
+
+                #(#searches)*
+                #(#values)*
+                #(#relations)*
+                #(#counters)*
+
+                if let crate::util::MaybeOwned::Owned(g) = g {
+                    collector.add_group(g);
+                }
+            }
+
+            #text_search
+        }
+    })
+}
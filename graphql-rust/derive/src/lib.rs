@@ -0,0 +1,57 @@
+// Derives the boilerplate of the graphql models. The generated code refers to
+// the items of the `graphql-rust` crate by their `crate::` paths, so the macros
+// are only usable from within it.
+mod entity;
+mod filter;
+mod util;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+// Generates the `COLUMN_*` constants and the `TypeInfo`, `WithId`, `FromRow`
+// and `FromJson` impls of a model.
+//
+// Every field is a column named after it, unless annotated with:
+// * `#[sakila(primary_key)]` - the column identifying the entity
+// * `#[sakila(column = "name")]` - the column has a different name
+// * `#[sakila(column = PATH)]` - the column name is an existing constant
+// * `#[sakila(resolves("field", ...))]` - the column is needed to resolve the
+//   listed graphql fields, e.g. a foreign key and its relation
+// * `#[sakila(computed)]` - the value is computed by the query and there is no
+//   column to select for it
+// * `#[sakila(json = PATH)]` - the function converting the prefetched json value
+// * `#[sakila(prefetched)]` - the field holding the prefetched relations
+// * `#[sakila(skip)]` - the field is not read from the database
+#[proc_macro_derive(SakilaEntity, attributes(sakila))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    entity::expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+// Generates the `InputFilter` impl of a filter.
+//
+// The struct is annotated with `#[sakila(table = "name", entity = PATH)]` and
+// any number of `join_table(...)` descriptions, which are referenced by name
+// from the fields.
+//
+// A field named `<column>_<operation>`, e.g. `title_starts_with`, filters the
+// `COLUMN_<COLUMN>` of the entity, unless annotated with:
+// * `#[sakila(column = EXPR, op = "name")]` - overrides the column or operation
+// * `#[sakila(and)]`, `#[sakila(or)]`, `#[sakila(not)]` - the nested filters
+// * `#[sakila(text_search = EXPR)]` - the full text search over a column
+// * `#[sakila(join_column(parent_col = EXPR, child_col = EXPR))]` - a filter of
+//   the entity referenced by a foreign key
+// * `#[sakila(join_table = "name")]` - a filter of the entities related by a
+//   join table, optionally followed by `every` or `none`
+// * `#[sakila(count_filter = "name")]` - restricts the counted related entities
+// * `#[sakila(count = "name")]` - compares the number of related entities
+// * `#[sakila(skip)]` - the field is handled elsewhere
+#[proc_macro_derive(SakilaFilter, attributes(sakila))]
+pub fn derive_filter(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    filter::expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
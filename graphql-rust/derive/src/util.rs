@@ -0,0 +1,60 @@
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{Attribute, Data, DeriveInput, Error, Field, Fields, Result};
+
+pub const ATTRIBUTE: &str = "sakila";
+
+pub fn named_fields(input: &DeriveInput) -> Result<impl Iterator<Item = &Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields.named.iter()),
+            _ => Err(Error::new_spanned(
+                input,
+                "expected a struct with named fields",
+            )),
+        },
+        _ => Err(Error::new_spanned(input, "expected a struct")),
+    }
+}
+
+pub fn sakila_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|a| a.path().is_ident(ATTRIBUTE))
+}
+
+// Whether the field is hidden from the graphql schema by `#[graphql(skip)]`
+pub fn is_graphql_skipped(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("graphql"))
+        .filter_map(|a| a.meta.require_list().ok())
+        .any(|list| contains_ident(list.tokens.clone(), "skip"))
+}
+
+fn contains_ident(tokens: TokenStream, ident: &str) -> bool {
+    tokens
+        .into_iter()
+        .any(|t| matches!(t, TokenTree::Ident(i) if i == ident))
+}
+
+// The name of the graphql field, e.g. `release_year` -> `releaseYear`
+pub fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        match c {
+            '_' => upper = !result.is_empty(),
+            c if upper => {
+                result.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+// The name of the column constant, e.g. `release_year` -> `COLUMN_RELEASE_YEAR`
+pub fn column_const(name: &str) -> String {
+    format!("COLUMN_{}", name.to_uppercase())
+}
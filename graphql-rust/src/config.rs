@@ -20,6 +20,34 @@ pub struct ServiceConfig {
     pub db: DatabaseConfig,
     pub server: ServerConfig,
     pub data_loader: DataLoaderConfig,
+    pub query: QueryConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryConfig {
+    pub max_page_size: u32,
+    pub execution: ExecutionMode,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            max_page_size: 100,
+            execution: ExecutionMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    // Every nested list is resolved by the data loaders
+    #[default]
+    DataLoader,
+
+    // The selection tree of the `films` and `actors` queries is compiled into a
+    // single statement, using lateral subqueries and JSON aggregation
+    SingleQuery,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +69,8 @@ impl Default for DataLoaderConfig {
 pub struct ServerConfig {
     pub port: u16,
     pub request_timeout: u64,
+    // in bytes, it also limits the size of the uploaded files
+    pub max_body_size: usize,
 }
 
 impl Default for ServerConfig {
@@ -48,6 +78,7 @@ impl Default for ServerConfig {
         Self {
             port: 8080,
             request_timeout: 10_000,
+            max_body_size: 16 * 1024 * 1024,
         }
     }
 }
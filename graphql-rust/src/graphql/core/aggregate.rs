@@ -0,0 +1,28 @@
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load, FromRow};
+use crate::query::{Aggregate, SqlVisitor, TableFilter};
+use async_graphql::Context;
+use deadpool_postgres::Pool;
+
+pub async fn aggregate<'c, T: FromRow, F: InputFilter>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    aggregate: &Aggregate<'_>,
+) -> anyhow::Result<Vec<T>> {
+    let mut tf = TableFilter::new(F::TABLE_NAME);
+    if let Some(filter) = filter.as_ref() {
+        filter.collect_into(tf.filter_group_mut());
+    }
+
+    let (sql, params) = SqlVisitor::new().translate_aggregate(&tf, aggregate);
+    tracing::debug!(
+        query=sql,
+        paramters=?params,
+        table=F::TABLE_NAME,
+    );
+
+    let db = ctx.data_unchecked::<Pool>();
+    let result = load(db, sql, params).await?;
+
+    Ok(result)
+}
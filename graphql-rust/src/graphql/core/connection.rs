@@ -0,0 +1,193 @@
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::graphql::core::loader::{load, load_columns, FromRow, WithId};
+use crate::graphql::core::query::{selected_columns, VALUE_ALIAS};
+use crate::query::{
+    Direction, Operation, OrderBy, OrderKey, Page, SqlVisitor, TableFilter, ValueFilter,
+};
+use async_graphql::connection::{Connection, Edge, EmptyFields, OpaqueCursor};
+use async_graphql::{Context, OutputType, SimpleObject};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct KeysetCursor {
+    pub id: i32,
+}
+
+pub type Cursor = OpaqueCursor<KeysetCursor>;
+
+#[derive(Debug, Default, Clone, SimpleObject)]
+pub struct ConnectionFields {
+    pub total_count: i64,
+}
+
+pub type EntityConnection<T> = Connection<Cursor, T, ConnectionFields, EmptyFields>;
+
+pub async fn connection<'c, T, F>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> async_graphql::Result<EntityConnection<T>>
+where
+    T: TypeInfo + FromRow + WithId<i32> + OutputType,
+    F: InputFilter,
+{
+    async_graphql::connection::query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<Cursor>, before: Option<Cursor>, first, last| async move {
+            let db = ctx.data_unchecked::<Pool>();
+            let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size as usize;
+
+            let backward = last.is_some();
+            let page_size = last.or(first).unwrap_or(max_page_size).min(max_page_size);
+
+            let mut tf = table_filter(filter);
+
+            let total_count = match ctx.look_ahead().field("totalCount").exists() {
+                false => 0,
+                true => {
+                    let (sql, params) = SqlVisitor::new().translate_count(&tf, VALUE_ALIAS);
+                    tracing::debug!(query=sql, paramters=?params, table=F::TABLE_NAME);
+
+                    let count: Vec<i64> = load(db, sql, params).await?;
+                    count.first().copied().unwrap_or_default()
+                }
+            };
+
+            if let Some(after) = after.as_ref() {
+                let f = ValueFilter::new(Operation::Gt, T::PRIMARY_KEY, &after.id);
+                tf.filter_group_mut().add_filter(f);
+            }
+
+            if let Some(before) = before.as_ref() {
+                let f = ValueFilter::new(Operation::Lt, T::PRIMARY_KEY, &before.id);
+                tf.filter_group_mut().add_filter(f);
+            }
+
+            let field = ctx.field();
+            let node_fields = field
+                .selection_set()
+                .filter(|f| f.name() == "edges")
+                .flat_map(|f| f.selection_set())
+                .filter(|f| f.name() == "node")
+                .chain(field.selection_set().filter(|f| f.name() == "nodes"))
+                .flat_map(|f| f.selection_set());
+
+            let mut selected = selected_columns::<T>(node_fields)?;
+            selected.push(T::PRIMARY_KEY);
+            selected.sort_unstable();
+            selected.dedup();
+
+            // fetch one extra row to find out if there are more pages
+            let limit = page_size as i64 + 1;
+            let direction = match backward {
+                true => Direction::Desc,
+                false => Direction::Asc,
+            };
+
+            let (sql, params) = SqlVisitor::new()
+                .order_by(vec![OrderBy {
+                    key: OrderKey::Column(T::PRIMARY_KEY),
+                    direction,
+                    nulls: None,
+                }])
+                .paginate(Page {
+                    limit: &limit,
+                    offset: None,
+                })
+                .translate(&tf, &selected);
+            tracing::debug!(query=sql, paramters=?params, table=F::TABLE_NAME);
+
+            let mut rows: Vec<T> = load_columns(db, sql, params, &selected).await?;
+            let has_more = rows.len() > page_size;
+            rows.truncate(page_size);
+            if backward {
+                rows.reverse();
+            }
+
+            // the rows behind the cursor are probed separately, because the cursor
+            // may point at a row which no longer matches the filter
+            let (has_previous_page, has_next_page) = match backward {
+                true => match before.as_ref() {
+                    None => (has_more, false),
+                    Some(before) => {
+                        let op = Operation::Gte;
+                        (
+                            has_more,
+                            exists_beyond::<T, F>(db, filter, op, before.id).await?,
+                        )
+                    }
+                },
+                false => match after.as_ref() {
+                    None => (false, has_more),
+                    Some(after) => {
+                        let op = Operation::Lte;
+                        (
+                            exists_beyond::<T, F>(db, filter, op, after.id).await?,
+                            has_more,
+                        )
+                    }
+                },
+            };
+
+            let mut connection = Connection::with_additional_fields(
+                has_previous_page,
+                has_next_page,
+                ConnectionFields { total_count },
+            );
+
+            connection.edges.extend(rows.into_iter().map(|node| {
+                let cursor = OpaqueCursor(KeysetCursor { id: *node.id() });
+                Edge::new(cursor, node)
+            }));
+
+            Ok::<_, anyhow::Error>(connection)
+        },
+    )
+    .await
+}
+
+fn table_filter<F: InputFilter>(filter: &Option<F>) -> TableFilter<'_> {
+    let mut tf = TableFilter::new(F::TABLE_NAME);
+    if let Some(filter) = filter.as_ref() {
+        filter.collect_into(tf.filter_group_mut());
+    }
+
+    tf
+}
+
+// Checks if any row matching the filter is on the given side of the cursor
+async fn exists_beyond<T, F>(
+    db: &Pool,
+    filter: &Option<F>,
+    operation: Operation,
+    cursor: i32,
+) -> anyhow::Result<bool>
+where
+    T: TypeInfo,
+    F: InputFilter,
+{
+    let mut tf = table_filter(filter);
+    let f = ValueFilter::new(operation, T::PRIMARY_KEY, &cursor);
+    tf.filter_group_mut().add_filter(f);
+
+    let limit = 1i64;
+    let select = format!("{} AS {}", T::PRIMARY_KEY, VALUE_ALIAS);
+    let (sql, params) = SqlVisitor::new()
+        .paginate(Page {
+            limit: &limit,
+            offset: None,
+        })
+        .translate(&tf, &[select.as_str()]);
+    tracing::debug!(query=sql, paramters=?params, table=F::TABLE_NAME);
+
+    let rows: Vec<i32> = load(db, sql, params).await?;
+    Ok(!rows.is_empty())
+}
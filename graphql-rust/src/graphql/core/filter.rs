@@ -1,5 +1,6 @@
 use crate::query::{
-    Combinator, FilterGroup, JoinColumnFilter, JoinTableFilter, Operation, TableFilter, ValueFilter,
+    Combinator, FilterGroup, JoinColumnFilter, JoinTableCountFilter, JoinTableFilter, Operation,
+    Quantifier, TableFilter, TextSearch, ValueFilter,
 };
 use crate::util::MaybeOwned;
 use rustc_hash::FxHashMap;
@@ -17,11 +18,26 @@ pub trait InputFilter {
     where
         Self: Sized;
 
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized;
+
     fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>);
+
+    fn text_search(&self) -> Option<TextSearch<'_>> {
+        None
+    }
 }
 
 pub trait TypeInfo {
+    const PRIMARY_KEY: &'static str;
     const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>>;
+    // the fields, which are computed by the query and have no column of their own
+    const COMPUTED_FIELDS: &'static [&'static str] = &[];
+
+    fn db_column(field: &str) -> Option<&'static str> {
+        Self::QUERY_FIELD_TO_DB_COLUMN_MAP.get(field).copied()
+    }
 }
 
 pub fn and_filters<'f, T: InputFilter>(g: &mut FilterGroup<'f>, f: &'f T) {
@@ -58,6 +74,14 @@ pub fn or_filters<'f, T: InputFilter>(g: &mut FilterGroup<'f>, f: &'f T) {
     }
 }
 
+pub fn not_filter<'f, T: InputFilter>(g: &mut FilterGroup<'f>, f: &'f T) {
+    if let Some(filter) = f.not_filter() {
+        let mut c = FilterGroup::new(Combinator::Not);
+        filter.collect_into(&mut c);
+        g.add_group(c);
+    }
+}
+
 fn op<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, operation: Operation, value: &'a Option<T>)
 where
     T: 'a + ToSql + Sync,
@@ -142,6 +166,15 @@ pub fn is_not_null<'a>(g: &mut FilterGroup<'a>, column: &'a str) {
     op(g, column, Operation::IsNotNull, &Some(""))
 }
 
+#[allow(unused)]
+pub fn null_check<'a>(g: &mut FilterGroup<'a>, column: &'a str, value: &Option<bool>) {
+    match value {
+        Some(true) => is_null(g, column),
+        Some(false) => is_not_null(g, column),
+        None => {}
+    }
+}
+
 #[allow(unused)]
 pub fn contains<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
 where
@@ -150,6 +183,62 @@ where
     op(g, column, Operation::Contains, value)
 }
 
+#[allow(unused)]
+pub fn i_contains<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::IContains, value)
+}
+
+#[allow(unused)]
+pub fn starts_with<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::StartsWith, value)
+}
+
+#[allow(unused)]
+pub fn ends_with<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::EndsWith, value)
+}
+
+#[allow(unused)]
+pub fn ilike<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::ILike, value)
+}
+
+#[allow(unused)]
+pub fn regex<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::Regex, value)
+}
+
+#[allow(unused)]
+pub fn array_contains<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::ArrayContains, value)
+}
+
+#[allow(unused)]
+pub fn text_search<'a, T>(g: &mut FilterGroup<'a>, column: &'a str, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    op(g, column, Operation::TextSearch, value)
+}
+
 #[allow(unused)]
 pub fn join_column<'a, C>(
     g: &mut FilterGroup<'a>,
@@ -179,11 +268,89 @@ pub fn join_table<'a, C>(
     child_join_col: &'a str,
 ) where
     C: InputFilter,
+{
+    quantified_join_table(
+        g,
+        child_filter,
+        Quantifier::Some,
+        join_table,
+        jt_parent_col,
+        jt_child_col,
+        parent_join_col,
+        child_join_col,
+    )
+}
+
+#[allow(unused)]
+pub fn join_table_every<'a, C>(
+    g: &mut FilterGroup<'a>,
+    child_filter: &'a Option<Box<C>>,
+    join_table: &'a str,
+    jt_parent_col: &'a str,
+    jt_child_col: &'a str,
+    parent_join_col: &'a str,
+    child_join_col: &'a str,
+) where
+    C: InputFilter,
+{
+    quantified_join_table(
+        g,
+        child_filter,
+        Quantifier::Every,
+        join_table,
+        jt_parent_col,
+        jt_child_col,
+        parent_join_col,
+        child_join_col,
+    )
+}
+
+#[allow(unused)]
+pub fn join_table_none<'a, C>(
+    g: &mut FilterGroup<'a>,
+    child_filter: &'a Option<Box<C>>,
+    join_table: &'a str,
+    jt_parent_col: &'a str,
+    jt_child_col: &'a str,
+    parent_join_col: &'a str,
+    child_join_col: &'a str,
+) where
+    C: InputFilter,
+{
+    quantified_join_table(
+        g,
+        child_filter,
+        Quantifier::None,
+        join_table,
+        jt_parent_col,
+        jt_child_col,
+        parent_join_col,
+        child_join_col,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn quantified_join_table<'a, C>(
+    g: &mut FilterGroup<'a>,
+    child_filter: &'a Option<Box<C>>,
+    quantifier: Quantifier,
+    join_table: &'a str,
+    jt_parent_col: &'a str,
+    jt_child_col: &'a str,
+    parent_join_col: &'a str,
+    child_join_col: &'a str,
+) where
+    C: InputFilter,
 {
     if let Some(child_filter) = child_filter.as_ref() {
         let mut tf = TableFilter::new(C::TABLE_NAME);
         child_filter.collect_into(tf.filter_group_mut());
 
+        // every related row trivially matches an empty filter
+        if quantifier == Quantifier::Every && tf.filter_group().is_empty() {
+            return;
+        }
+
         let jf = JoinTableFilter::new(
             tf,
             join_table,
@@ -191,7 +358,91 @@ pub fn join_table<'a, C>(
             jt_parent_col,
             child_join_col,
             jt_child_col,
-        );
+        )
+        .quantified(quantifier);
         g.add_filter(jf);
     }
 }
+
+// The rows of a join table to be counted, optionally restricted to the ones
+// referencing child rows that match the filter
+#[allow(unused)]
+pub fn join_table_counter<'a, C>(
+    count_filter: &'a Option<Box<C>>,
+    join_table: &'a str,
+    jt_parent_col: &'a str,
+    jt_child_col: &'a str,
+    parent_join_col: &'a str,
+    child_join_col: &'a str,
+) -> JoinTableFilter<'a>
+where
+    C: InputFilter,
+{
+    let mut tf = TableFilter::new(C::TABLE_NAME);
+    if let Some(count_filter) = count_filter.as_ref() {
+        count_filter.collect_into(tf.filter_group_mut());
+    }
+
+    JoinTableFilter::new(
+        tf,
+        join_table,
+        parent_join_col,
+        jt_parent_col,
+        child_join_col,
+        jt_child_col,
+    )
+}
+
+fn count_op<'a, T>(
+    g: &mut FilterGroup<'a>,
+    join: &JoinTableFilter<'a>,
+    operation: Operation,
+    value: &'a Option<T>,
+) where
+    T: 'a + ToSql + Sync,
+{
+    if let Some(value) = value.as_ref() {
+        let f = JoinTableCountFilter::new(join.clone(), operation, value);
+        g.add_filter(f);
+    }
+}
+
+#[allow(unused)]
+pub fn count_eq<'a, T>(g: &mut FilterGroup<'a>, join: &JoinTableFilter<'a>, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    count_op(g, join, Operation::Eq, value)
+}
+
+#[allow(unused)]
+pub fn count_gt<'a, T>(g: &mut FilterGroup<'a>, join: &JoinTableFilter<'a>, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    count_op(g, join, Operation::Gt, value)
+}
+
+#[allow(unused)]
+pub fn count_gte<'a, T>(g: &mut FilterGroup<'a>, join: &JoinTableFilter<'a>, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    count_op(g, join, Operation::Gte, value)
+}
+
+#[allow(unused)]
+pub fn count_lt<'a, T>(g: &mut FilterGroup<'a>, join: &JoinTableFilter<'a>, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    count_op(g, join, Operation::Lt, value)
+}
+
+#[allow(unused)]
+pub fn count_lte<'a, T>(g: &mut FilterGroup<'a>, join: &JoinTableFilter<'a>, value: &'a Option<T>)
+where
+    T: 'a + ToSql + Sync,
+{
+    count_op(g, join, Operation::Lte, value)
+}
@@ -1,24 +1,64 @@
+use crate::graphql::core::filter::TypeInfo;
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::query::{selected_columns, VALUE_ALIAS};
+use crate::query::{Operation, SqlVisitor, TableFilter, ValueFilter};
 use anyhow::Error;
 use async_graphql::futures_util::StreamExt;
+use async_graphql::{Context, InputType};
 use deadpool_postgres::{GenericClient, Pool};
+use rustc_hash::FxHashMap;
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
 use std::pin::pin;
+use std::sync::Arc;
 use tokio_postgres::types::{FromSqlOwned, ToSql};
 use tokio_postgres::Row;
 
 pub trait FromRow {
-    fn from_row(row: &Row) -> Result<Self, Error>
+    // The columns mapped by `from_row`
+    const COLUMNS: &'static [&'static str];
+
+    // Maps the given columns, the fields of the other columns are left with
+    // their default values. A column missing from the row, or not mapped to a
+    // field, is an error.
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
     where
         Self: Sized;
+
+    fn from_row(row: &Row) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::from_columns(row, Self::COLUMNS)
+    }
 }
 
 impl FromRow for i32 {
-    fn from_row(row: &Row) -> Result<Self, Error>
+    const COLUMNS: &'static [&'static str] = &[VALUE_ALIAS];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        match columns {
+            [column] => Ok(row.try_get(column)?),
+            _ => anyhow::bail!("expected a single column, found: {:?}", columns),
+        }
+    }
+}
+
+impl FromRow for i64 {
+    const COLUMNS: &'static [&'static str] = &[VALUE_ALIAS];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        Ok(row.try_get("__value")?)
+        match columns {
+            [column] => Ok(row.try_get(column)?),
+            _ => anyhow::bail!("expected a single column, found: {:?}", columns),
+        }
     }
 }
 
@@ -26,10 +66,35 @@ pub trait WithId<ID> {
     fn id(&self) -> &ID;
 }
 
+// The entity referenced by a non-null foreign key. A missing row is reported
+// instead of being replaced by a default value.
+pub fn referenced<T>(value: Option<T>, entity: &str, id: i32) -> async_graphql::Result<T> {
+    value.ok_or_else(|| format!("the {} {} does not exist", entity, id).into())
+}
+
 pub async fn load<T: FromRow>(
     pool: &Pool,
     query: String,
     params: Vec<&(dyn ToSql + Sync)>,
+) -> Result<Vec<T>, anyhow::Error> {
+    load_rows(pool, query, params, T::from_row).await
+}
+
+// Same as `load`, but only for the requested columns of the type
+pub async fn load_columns<T: FromRow>(
+    pool: &Pool,
+    query: String,
+    params: Vec<&(dyn ToSql + Sync)>,
+    columns: &[&str],
+) -> Result<Vec<T>, anyhow::Error> {
+    load_rows(pool, query, params, |row| T::from_columns(row, columns)).await
+}
+
+async fn load_rows<T>(
+    pool: &Pool,
+    query: String,
+    params: Vec<&(dyn ToSql + Sync)>,
+    map: impl Fn(&Row) -> Result<T, Error>,
 ) -> Result<Vec<T>, anyhow::Error> {
     let con = pool.get().await?;
     let stm = con.prepare_cached(&query).await?;
@@ -40,34 +105,33 @@ pub async fn load<T: FromRow>(
     let mut result = vec![];
     while let Some(row) = rows.next().await {
         let row = row?;
-        let value = T::from_row(&row)?;
-        result.push(value);
+        result.push(map(&row)?);
     }
 
     Ok(result)
 }
 
-pub async fn load_one_by_key<I, T, K>(
+pub async fn load_one_by_key<I, T>(
     pool: &Pool,
     query: &str,
-    keys: &[K],
+    params: Vec<&(dyn ToSql + Sync)>,
+    columns: &[&str],
 ) -> anyhow::Result<HashMap<I, T>>
 where
     I: Clone + Eq + Hash,
     T: WithId<I> + FromRow,
-    K: ToSql + Sync,
 {
     let con = pool.get().await?;
-    let stm = con.prepare_cached(&query).await?;
+    let stm = con.prepare_cached(query).await?;
 
-    let rows = con.query_raw(&stm, &[keys]).await?;
+    let rows = con.query_raw(&stm, params).await?;
     let mut rows = pin!(rows);
 
     let mut result = HashMap::new();
 
     while let Some(row) = rows.next().await {
         let row = row?;
-        let value = T::from_row(&row)?;
+        let value = T::from_columns(&row, columns)?;
         result.insert(value.id().to_owned(), value);
     }
 
@@ -85,11 +149,25 @@ where
     T: FromRow,
     KEYS: ToSql + Sync,
     ID: FromSqlOwned + Sync + Clone + Eq + Hash + 'static,
+{
+    load_many_by_key_with_params(pool, query, vec![&keys]).await
+}
+
+// Same as `load_many_by_key`, but for queries which take more parameters
+// than just the list of keys
+pub async fn load_many_by_key_with_params<T, ID>(
+    pool: &Pool,
+    query: &str,
+    params: Vec<&(dyn ToSql + Sync)>,
+) -> anyhow::Result<HashMap<ID, Vec<T>>>
+where
+    T: FromRow,
+    ID: FromSqlOwned + Sync + Clone + Eq + Hash + 'static,
 {
     let con = pool.get().await?;
-    let stm = con.prepare_cached(&query).await?;
+    let stm = con.prepare_cached(query).await?;
 
-    let rows = con.query_raw(&stm, &[keys]).await?;
+    let rows = con.query_raw(&stm, params).await?;
     let mut rows = pin!(rows);
 
     let mut result = HashMap::new();
@@ -105,3 +183,230 @@ where
 
     Ok(result)
 }
+
+pub async fn load_value_by_key<T, KEYS, ID>(
+    pool: &Pool,
+    query: &str,
+    keys: &[KEYS],
+) -> anyhow::Result<HashMap<ID, T>>
+where
+    T: FromRow,
+    KEYS: ToSql + Sync,
+    ID: FromSqlOwned + Sync + Clone + Eq + Hash + 'static,
+{
+    let con = pool.get().await?;
+    let stm = con.prepare_cached(query).await?;
+
+    let rows = con.query_raw(&stm, &[keys]).await?;
+    let mut rows = pin!(rows);
+
+    let mut result = HashMap::new();
+
+    while let Some(row) = rows.next().await {
+        let row = row?;
+
+        let key: ID = row.try_get(KEY_BULK_LOAD)?;
+        let value = T::from_row(&row)?;
+
+        result.insert(key, value);
+    }
+
+    Ok(result)
+}
+
+// The key of the loaders for nested lists with arguments. The keys with equal
+// arguments are loaded by a single query.
+#[derive(Debug)]
+pub struct FilteredKey<F, O> {
+    pub id: i32,
+    pub args: Arc<ListArgs<F, O>>,
+}
+
+impl<F: InputType, O: InputType> FilteredKey<F, O> {
+    pub fn new(
+        id: i32,
+        filter: Option<F>,
+        order_by: Option<Vec<O>>,
+        page: PageArgs,
+        max_page_size: u32,
+    ) -> Self {
+        // the inputs are compared by their GraphQL value, which lists the
+        // fields of an input object in the order of their declaration
+        let fingerprint = format!("{} {}", filter.to_value(), order_by.to_value());
+
+        Self {
+            id,
+            args: Arc::new(ListArgs {
+                filter,
+                order_by,
+                limit: page.limit(max_page_size),
+                offset: page.offset(),
+                fingerprint,
+            }),
+        }
+    }
+}
+
+impl<F, O> Clone for FilteredKey<F, O> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            args: self.args.clone(),
+        }
+    }
+}
+
+impl<F, O> PartialEq for FilteredKey<F, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.args == other.args
+    }
+}
+
+impl<F, O> Eq for FilteredKey<F, O> {}
+
+impl<F, O> Hash for FilteredKey<F, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.args.hash(state);
+    }
+}
+
+#[derive(Debug)]
+pub struct ListArgs<F, O> {
+    pub filter: Option<F>,
+    pub order_by: Option<Vec<O>>,
+    pub limit: i64,
+    pub offset: Option<i64>,
+    fingerprint: String,
+}
+
+impl<F, O> PartialEq for ListArgs<F, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint == other.fingerprint
+            && self.limit == other.limit
+            && self.offset == other.offset
+    }
+}
+
+impl<F, O> Eq for ListArgs<F, O> {}
+
+impl<F, O> Hash for ListArgs<F, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fingerprint.hash(state);
+        self.limit.hash(state);
+        self.offset.hash(state);
+    }
+}
+
+// The key of the entity loaders. Only the columns requested by the query are
+// fetched, so the same entity may be loaded once per distinct column set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnsKey {
+    pub id: i32,
+    pub columns: Arc<[&'static str]>,
+}
+
+impl ColumnsKey {
+    pub fn new<T: TypeInfo>(ctx: &Context<'_>, id: i32) -> anyhow::Result<Self> {
+        Ok(Self {
+            id,
+            columns: requested_columns::<T>(ctx)?,
+        })
+    }
+
+    pub fn many<T: TypeInfo>(ctx: &Context<'_>, ids: &[i32]) -> anyhow::Result<Vec<Self>> {
+        let columns = requested_columns::<T>(ctx)?;
+        Ok(ids
+            .iter()
+            .map(|&id| Self {
+                id,
+                columns: columns.clone(),
+            })
+            .collect())
+    }
+}
+
+fn requested_columns<T: TypeInfo>(ctx: &Context<'_>) -> anyhow::Result<Arc<[&'static str]>> {
+    let mut columns = selected_columns::<T>(ctx.field().selection_set())?;
+    if !columns.contains(&T::PRIMARY_KEY) {
+        columns.push(T::PRIMARY_KEY);
+        columns.sort_unstable();
+    }
+
+    Ok(columns.into())
+}
+
+pub async fn load_columns_by_key<T>(
+    pool: &Pool,
+    table: &str,
+    keys: &[ColumnsKey],
+) -> anyhow::Result<HashMap<ColumnsKey, T>>
+where
+    T: TypeInfo + FromRow + WithId<i32>,
+{
+    let mut groups = FxHashMap::<Arc<[&'static str]>, Vec<i32>>::default();
+    for key in keys {
+        groups.entry(key.columns.clone()).or_default().push(key.id);
+    }
+
+    let mut result = HashMap::with_capacity(keys.len());
+    for (columns, ids) in groups {
+        let mut tf = TableFilter::new(table);
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::In, T::PRIMARY_KEY, &ids));
+
+        let (sql, params) = SqlVisitor::new().translate(&tf, &columns);
+        tracing::debug!(query=sql, paramters=?params, table=table);
+
+        let mut rows: HashMap<i32, T> = load_one_by_key(pool, &sql, params, &columns).await?;
+
+        for id in ids {
+            if let Some(value) = rows.remove(&id) {
+                let key = ColumnsKey {
+                    id,
+                    columns: columns.clone(),
+                };
+                result.insert(key, value);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn key(id: i32, filter: &str, first: Option<i32>) -> FilteredKey<String, i32> {
+        let page = PageArgs::new(first, None);
+        FilteredKey::new(id, Some(filter.to_owned()), Some(vec![1, 2]), page, 100)
+    }
+
+    #[test]
+    fn keys_with_equal_arguments_share_a_query() {
+        let keys = [key(1, "A", None), key(2, "A", None), key(1, "A", None)];
+
+        assert_eq!(keys[0], keys[2]);
+        assert_eq!(keys[0].args, keys[1].args);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 2);
+    }
+
+    #[test]
+    fn keys_differ_by_filter_order_and_page() {
+        let base = key(1, "A", None);
+        let ordered = FilteredKey::<String, i32>::new(
+            1,
+            Some("A".to_owned()),
+            Some(vec![2, 1]),
+            PageArgs::default(),
+            100,
+        );
+
+        assert_ne!(base.args, key(1, "B", None).args);
+        assert_ne!(base.args, key(1, "A", Some(10)).args);
+        assert_ne!(base.args, ordered.args);
+        assert_eq!(base.args.limit, 100);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod aggregate;
+pub mod connection;
 pub mod filter;
 pub mod loader;
+pub mod mutation;
+pub mod order;
+pub mod page;
+pub mod prefetch;
 pub mod query;
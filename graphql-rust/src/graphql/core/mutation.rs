@@ -0,0 +1,352 @@
+use crate::graphql::core::filter::TypeInfo;
+use crate::graphql::core::loader::FromRow;
+use async_graphql::{ErrorExtensions, ScalarType};
+use deadpool_postgres::Transaction;
+use rust_decimal::Decimal;
+use std::fmt::{Display, Formatter};
+use time::OffsetDateTime;
+use tokio_postgres::types::ToSql;
+
+// The columns written by an INSERT or UPDATE. Only the provided values are
+// written, so the omitted columns keep their defaults or current values.
+#[derive(Default)]
+pub struct Assignments<'a> {
+    // the column and the type, to which its parameter is cast
+    columns: Vec<(&'static str, Option<&'static str>)>,
+    params: Vec<&'a (dyn ToSql + Sync)>,
+}
+
+impl<'a> Assignments<'a> {
+    pub fn set(&mut self, column: &'static str, value: &'a (dyn ToSql + Sync)) {
+        self.columns.push((column, None));
+        self.params.push(value);
+    }
+
+    // Domain columns (e.g. `year`) do not accept the parameters of their base
+    // type, unless they are explicitly cast to it
+    pub fn set_opt_as<T: ToSql + Sync>(
+        &mut self,
+        column: &'static str,
+        value: &'a Option<T>,
+        sql_type: &'static str,
+    ) {
+        if let Some(value) = value.as_ref() {
+            self.columns.push((column, Some(sql_type)));
+            self.params.push(value);
+        }
+    }
+
+    pub fn set_opt<T: ToSql + Sync>(&mut self, column: &'static str, value: &'a Option<T>) {
+        if let Some(value) = value.as_ref() {
+            self.set(column, value);
+        }
+    }
+
+    // INSERT INTO table (a, b) VALUES ($1, $2) RETURNING ...
+    pub fn insert<T: FromRow>(self, table: &str) -> (String, Vec<&'a (dyn ToSql + Sync)>) {
+        let columns = self
+            .columns
+            .iter()
+            .map(|(column, _)| *column)
+            .collect::<Vec<_>>();
+        let values = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, sql_type))| placeholder(idx + 1, *sql_type))
+            .collect::<Vec<_>>();
+
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+            table,
+            columns.join(", "),
+            values.join(", "),
+            returned_columns::<T>(),
+        );
+
+        (sql, self.params)
+    }
+
+    // UPDATE table SET a = $2, b = $3 WHERE id = $1 RETURNING ...
+    pub fn update<T: TypeInfo + FromRow>(
+        self,
+        table: &str,
+        id: &'a (dyn ToSql + Sync),
+    ) -> (String, Vec<&'a (dyn ToSql + Sync)>) {
+        let mut assignments = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (column, sql_type))| {
+                format!("{} = {}", column, placeholder(idx + 2, *sql_type))
+            })
+            .collect::<Vec<_>>();
+        assignments.push("last_update = now()".to_owned());
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} = $1 RETURNING {}",
+            table,
+            assignments.join(", "),
+            T::PRIMARY_KEY,
+            returned_columns::<T>(),
+        );
+
+        let mut params = Vec::with_capacity(self.params.len() + 1);
+        params.push(id);
+        params.extend(self.params);
+        (sql, params)
+    }
+}
+
+fn placeholder(idx: usize, sql_type: Option<&str>) -> String {
+    match sql_type {
+        Some(sql_type) => format!("${}::{}", idx, sql_type),
+        None => format!("${}", idx),
+    }
+}
+
+// All columns of the type, as expected by its `FromRow` impl
+pub fn returned_columns<T: FromRow>() -> String {
+    T::COLUMNS.join(", ")
+}
+
+// Locks the row until the end of the transaction. When the client passed the
+// `last_update` of the row it has read, the row must not have changed since.
+pub async fn lock_version<T: TypeInfo>(
+    tx: &Transaction<'_>,
+    table: &str,
+    entity: &'static str,
+    id: i32,
+    expected_last_update: Option<OffsetDateTime>,
+) -> async_graphql::Result<()> {
+    let sql = format!(
+        "SELECT last_update FROM {} WHERE {} = $1 FOR UPDATE",
+        table,
+        T::PRIMARY_KEY,
+    );
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let Some(row) = tx.query_opt(&stm, &[&id]).await? else {
+        return Err(MutationError::NotFound { entity, id }.extend());
+    };
+
+    let last_update: OffsetDateTime = row.try_get(0)?;
+    match expected_last_update {
+        Some(expected) if expected != last_update => {
+            Err(MutationError::Conflict { entity, id }.extend())
+        }
+        _ => Ok(()),
+    }
+}
+
+// The ids, which do not match a row of the table
+pub async fn missing_ids<T: TypeInfo>(
+    tx: &Transaction<'_>,
+    table: &str,
+    ids: &[i32],
+) -> anyhow::Result<Vec<i32>> {
+    let sql = format!(
+        "SELECT {id} FROM {} WHERE {id} = ANY ($1)",
+        table,
+        id = T::PRIMARY_KEY,
+    );
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let mut found = vec![];
+    for row in tx.query(&stm, &[&ids]).await? {
+        found.push(row.try_get::<_, i32>(0)?);
+    }
+
+    let mut missing = ids
+        .iter()
+        .copied()
+        .filter(|id| !found.contains(id))
+        .collect::<Vec<_>>();
+    missing.sort_unstable();
+    missing.dedup();
+    Ok(missing)
+}
+
+// The expected failures of the mutations. Besides the message, they carry a
+// `code` extension (and the ids involved), so the clients can handle them
+// without parsing the message.
+#[derive(Debug, Clone)]
+pub enum MutationError {
+    NotFound {
+        entity: &'static str,
+        id: i32,
+    },
+    NotFoundMany {
+        entity: &'static str,
+        ids: Vec<i32>,
+    },
+    NoCopyInStock {
+        film_id: i32,
+        store_id: i32,
+    },
+    OverdueRentals {
+        customer_id: i32,
+        count: i64,
+    },
+    CustomerInactive {
+        customer_id: i32,
+    },
+    StaffNotInStore {
+        staff_id: i32,
+        store_id: i32,
+    },
+    AlreadyReturned {
+        rental_id: i32,
+    },
+    InvalidAmount {
+        amount: Decimal,
+    },
+    PaymentDateOutOfRange {
+        rental_id: i32,
+        payment_date: OffsetDateTime,
+    },
+    Conflict {
+        entity: &'static str,
+        id: i32,
+    },
+    HasDependents {
+        entity: &'static str,
+        id: i32,
+        dependents: &'static str,
+        count: i64,
+    },
+}
+
+impl MutationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MutationError::NotFound { .. } | MutationError::NotFoundMany { .. } => "NOT_FOUND",
+            MutationError::NoCopyInStock { .. } => "NO_COPY_IN_STOCK",
+            MutationError::OverdueRentals { .. } => "OVERDUE_RENTALS",
+            MutationError::CustomerInactive { .. } => "CUSTOMER_INACTIVE",
+            MutationError::StaffNotInStore { .. } => "STAFF_NOT_IN_STORE",
+            MutationError::AlreadyReturned { .. } => "ALREADY_RETURNED",
+            MutationError::InvalidAmount { .. } => "INVALID_AMOUNT",
+            MutationError::PaymentDateOutOfRange { .. } => "PAYMENT_DATE_OUT_OF_RANGE",
+            MutationError::Conflict { .. } => "CONFLICT",
+            MutationError::HasDependents { .. } => "HAS_DEPENDENTS",
+        }
+    }
+}
+
+impl Display for MutationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MutationError::NotFound { entity, id } => {
+                write!(f, "the {} {} does not exist", entity, id)
+            }
+            MutationError::NotFoundMany { entity, ids } => {
+                write!(f, "the {} ids {:?} do not exist", entity, ids)
+            }
+            MutationError::NoCopyInStock { film_id, store_id } => write!(
+                f,
+                "no copy of the film {} is in stock at the store {}",
+                film_id, store_id
+            ),
+            MutationError::OverdueRentals { customer_id, count } => write!(
+                f,
+                "the customer {} has {} overdue rentals",
+                customer_id, count
+            ),
+            MutationError::CustomerInactive { customer_id } => {
+                write!(f, "the customer {} is not active", customer_id)
+            }
+            MutationError::StaffNotInStore { staff_id, store_id } => write!(
+                f,
+                "the staff member {} does not work at the store {}",
+                staff_id, store_id
+            ),
+            MutationError::AlreadyReturned { rental_id } => {
+                write!(f, "the rental {} is already returned", rental_id)
+            }
+            MutationError::InvalidAmount { amount } => {
+                write!(f, "the amount {} must be positive", amount)
+            }
+            MutationError::PaymentDateOutOfRange {
+                rental_id,
+                payment_date,
+            } => write!(
+                f,
+                "the payment of the rental {} dated {} is not covered by a partition of the payment table",
+                rental_id, payment_date
+            ),
+            MutationError::Conflict { entity, id } => write!(
+                f,
+                "the {} {} was changed since it was read, reload it and try again",
+                entity, id
+            ),
+            MutationError::HasDependents {
+                entity,
+                id,
+                dependents,
+                count,
+            } => write!(
+                f,
+                "the {} {} is still referenced by {} {}",
+                entity, id, count, dependents
+            ),
+        }
+    }
+}
+
+impl ErrorExtensions for MutationError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| {
+            e.set("code", self.code());
+            match self {
+                MutationError::NotFound { entity, id } | MutationError::Conflict { entity, id } => {
+                    e.set("entity", *entity);
+                    e.set("id", *id);
+                }
+                MutationError::NotFoundMany { entity, ids } => {
+                    e.set("entity", *entity);
+                    e.set("ids", ids.clone());
+                }
+                MutationError::NoCopyInStock { film_id, store_id } => {
+                    e.set("filmId", *film_id);
+                    e.set("storeId", *store_id);
+                }
+                MutationError::OverdueRentals { customer_id, count } => {
+                    e.set("customerId", *customer_id);
+                    e.set("count", *count);
+                }
+                MutationError::CustomerInactive { customer_id } => {
+                    e.set("customerId", *customer_id);
+                }
+                MutationError::StaffNotInStore { staff_id, store_id } => {
+                    e.set("staffId", *staff_id);
+                    e.set("storeId", *store_id);
+                }
+                MutationError::AlreadyReturned { rental_id } => {
+                    e.set("rentalId", *rental_id);
+                }
+                MutationError::InvalidAmount { amount } => {
+                    e.set("amount", amount.to_value());
+                }
+                MutationError::PaymentDateOutOfRange {
+                    rental_id,
+                    payment_date,
+                } => {
+                    e.set("rentalId", *rental_id);
+                    e.set("paymentDate", payment_date.to_value());
+                }
+                MutationError::HasDependents {
+                    entity,
+                    id,
+                    dependents,
+                    count,
+                } => {
+                    e.set("entity", *entity);
+                    e.set("id", *id);
+                    e.set("dependents", *dependents);
+                    e.set("count", *count);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,224 @@
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::query::{Direction, Nulls, OrderBy, OrderKey};
+use async_graphql::Enum;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum OrderDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+pub enum SortKey {
+    Field(&'static str),
+    Related(OrderKey<'static>),
+    Relevance,
+}
+
+pub trait OrderInput {
+    fn key(&self) -> SortKey;
+
+    fn direction(&self) -> OrderDirection;
+
+    fn nulls(&self) -> Option<NullsOrder>;
+}
+
+pub fn sort_order<T, O>(order: &Option<Vec<O>>) -> async_graphql::Result<Vec<OrderBy<'static>>>
+where
+    T: TypeInfo,
+    O: OrderInput,
+{
+    let Some(order) = order.as_ref() else {
+        return Ok(vec![]);
+    };
+
+    let mut result = Vec::with_capacity(order.len() + 1);
+    for o in order {
+        let key = match o.key() {
+            SortKey::Related(key) => key,
+            SortKey::Relevance => OrderKey::Rank,
+            SortKey::Field(field) => match T::db_column(field) {
+                Some(column) => OrderKey::Column(column),
+                None => return Err(format!("cannot order by field '{}'", field).into()),
+            },
+        };
+
+        let direction = match o.direction() {
+            OrderDirection::Asc => Direction::Asc,
+            OrderDirection::Desc => Direction::Desc,
+        };
+
+        let nulls = o.nulls().map(|n| match n {
+            NullsOrder::First => Nulls::First,
+            NullsOrder::Last => Nulls::Last,
+        });
+
+        result.push(OrderBy {
+            key,
+            direction,
+            nulls,
+        });
+    }
+
+    // use the primary key as a tie-breaker, so that the order is stable across pages
+    let has_primary_key = result
+        .iter()
+        .any(|o| matches!(o.key, OrderKey::Column(c) if c == T::PRIMARY_KEY));
+
+    if !result.is_empty() && !has_primary_key {
+        result.push(OrderBy {
+            key: OrderKey::Column(T::PRIMARY_KEY),
+            direction: Direction::Asc,
+            nulls: None,
+        });
+    }
+
+    Ok(result)
+}
+
+#[allow(unused)]
+pub fn join_column_key<C>(
+    parent_col: &'static str,
+    child_col: &'static str,
+    column: &'static str,
+) -> SortKey
+where
+    C: InputFilter,
+{
+    SortKey::Related(OrderKey::JoinColumn {
+        table: C::TABLE_NAME,
+        parent_column: parent_col,
+        child_column: child_col,
+        column,
+    })
+}
+
+#[allow(unused)]
+pub fn join_table_count_key(
+    join_table: &'static str,
+    jt_parent_col: &'static str,
+    parent_join_col: &'static str,
+) -> SortKey {
+    SortKey::Related(OrderKey::JoinTableCount {
+        join_table,
+        parent_table_join_col: parent_join_col,
+        join_table_parent_col: jt_parent_col,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hash::FxHashMap;
+    use std::sync::LazyLock;
+
+    struct Film;
+
+    impl TypeInfo for Film {
+        const PRIMARY_KEY: &'static str = "film_id";
+        const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+            LazyLock::new(|| FxHashMap::from_iter([("filmId", "film_id"), ("title", "title")]));
+    }
+
+    enum Field {
+        FilmId,
+        Title,
+        Unknown,
+        LanguageName,
+        ActorCount,
+    }
+
+    struct Order(Field, OrderDirection);
+
+    impl OrderInput for Order {
+        fn key(&self) -> SortKey {
+            match self.0 {
+                Field::FilmId => SortKey::Field("filmId"),
+                Field::Title => SortKey::Field("title"),
+                Field::Unknown => SortKey::Field("unknown"),
+                Field::LanguageName => SortKey::Related(OrderKey::JoinColumn {
+                    table: "language",
+                    parent_column: "language_id",
+                    child_column: "language_id",
+                    column: "name",
+                }),
+                Field::ActorCount => {
+                    join_table_count_key("film_actor", "film_id", Film::PRIMARY_KEY)
+                }
+            }
+        }
+
+        fn direction(&self) -> OrderDirection {
+            self.1
+        }
+
+        fn nulls(&self) -> Option<NullsOrder> {
+            None
+        }
+    }
+
+    fn keys(order: Vec<Order>) -> Vec<String> {
+        sort_order::<Film, _>(&Some(order))
+            .unwrap()
+            .iter()
+            .map(|o| match o.key {
+                OrderKey::Column(c) => format!("{} {}", c, o.direction),
+                OrderKey::JoinColumn { table, column, .. } => {
+                    format!("{}.{} {}", table, column, o.direction)
+                }
+                OrderKey::JoinTableCount { join_table, .. } => {
+                    format!("count({}) {}", join_table, o.direction)
+                }
+                OrderKey::Rank => format!("rank {}", o.direction),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn primary_key_breaks_ties() {
+        assert_eq!(
+            keys(vec![Order(Field::Title, OrderDirection::Desc)]),
+            ["title DESC", "film_id ASC"]
+        );
+    }
+
+    #[test]
+    fn explicit_primary_key_is_not_repeated() {
+        assert_eq!(
+            keys(vec![
+                Order(Field::FilmId, OrderDirection::Desc),
+                Order(Field::Title, OrderDirection::Asc),
+            ]),
+            ["film_id DESC", "title ASC"]
+        );
+    }
+
+    #[test]
+    fn related_keys_are_followed_by_the_primary_key() {
+        assert_eq!(
+            keys(vec![
+                Order(Field::LanguageName, OrderDirection::Asc),
+                Order(Field::ActorCount, OrderDirection::Desc),
+            ]),
+            ["language.name ASC", "count(film_actor) DESC", "film_id ASC"]
+        );
+    }
+
+    #[test]
+    fn no_tie_breaker_without_order() {
+        assert!(sort_order::<Film, Order>(&None).unwrap().is_empty());
+        assert!(keys(vec![]).is_empty());
+    }
+
+    #[test]
+    fn unknown_field_is_rejected() {
+        let order = Some(vec![Order(Field::Unknown, OrderDirection::Asc)]);
+        assert!(sort_order::<Film, _>(&order).is_err());
+    }
+}
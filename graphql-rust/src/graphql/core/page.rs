@@ -0,0 +1,49 @@
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PageArgs {
+    first: Option<i32>,
+    offset: Option<i32>,
+}
+
+impl PageArgs {
+    pub fn new(first: Option<i32>, offset: Option<i32>) -> Self {
+        Self { first, offset }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none() && self.offset.is_none()
+    }
+
+    pub fn limit(&self, max_page_size: u32) -> i64 {
+        let max_page_size = max_page_size as i64;
+
+        match self.first {
+            None => max_page_size,
+            Some(first) => (first.max(0) as i64).min(max_page_size),
+        }
+    }
+
+    pub fn offset(&self) -> Option<i64> {
+        self.offset.filter(|&o| o > 0).map(|o| o as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_is_capped_by_max_page_size() {
+        assert_eq!(PageArgs::new(None, None).limit(100), 100);
+        assert_eq!(PageArgs::new(Some(10), None).limit(100), 10);
+        assert_eq!(PageArgs::new(Some(500), None).limit(100), 100);
+        assert_eq!(PageArgs::new(Some(-1), None).limit(100), 0);
+    }
+
+    #[test]
+    fn only_positive_offsets_are_applied() {
+        assert_eq!(PageArgs::new(None, Some(20)).offset(), Some(20));
+        assert_eq!(PageArgs::new(None, Some(0)).offset(), None);
+        assert_eq!(PageArgs::new(None, Some(-5)).offset(), None);
+        assert_eq!(PageArgs::new(Some(10), None).offset(), None);
+    }
+}
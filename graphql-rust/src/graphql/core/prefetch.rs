@@ -0,0 +1,267 @@
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::graphql::core::loader::{load, FromRow};
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::query::{search_order, selected_columns, VALUE_ALIAS};
+use crate::query::{OrderBy, Page, ProjectedRelation, Projection, SqlVisitor, TableFilter};
+use anyhow::{anyhow, Error};
+use async_graphql::{Context, SelectionField};
+use deadpool_postgres::Pool;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+
+pub type JsonObject = serde_json::Map<String, Value>;
+
+pub trait FromJson {
+    // Maps the given columns of the object, like `FromRow::from_columns`. A
+    // column missing from the object, or not mapped to a field, is an error.
+    fn from_json(object: JsonObject, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+pub trait Prefetch: TypeInfo + FromJson {
+    // The related entities of the field, if they can be loaded by the same query
+    fn relation<'s>(
+        field: SelectionField<'s>,
+        limit: &'s (dyn ToSql + Sync),
+    ) -> Result<Option<ProjectedRelation<'s>>, Error>;
+}
+
+// The related entities, which were loaded along with their parent
+#[derive(Debug, Clone, Default)]
+pub struct Prefetched(Option<Arc<JsonObject>>);
+
+impl Prefetched {
+    pub fn from_json(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Object(object) => Ok(Self(Some(Arc::new(object)))),
+            value => Err(anyhow!("expected prefetched entities, found: {}", value)),
+        }
+    }
+
+    // The entities of the field being resolved, unless they were not prefetched
+    pub fn many<T: Prefetch>(&self, ctx: &Context<'_>) -> Result<Option<Vec<T>>, Error> {
+        let Some(value) = self.get(ctx) else {
+            return Ok(None);
+        };
+
+        let Value::Array(values) = value else {
+            return Err(anyhow!("expected a list of entities, found: {}", value));
+        };
+
+        let columns = projected_columns::<T>(ctx.field())?;
+        values
+            .iter()
+            .cloned()
+            .map(json_object)
+            .map(|o| o.and_then(|o| T::from_json(o, &columns)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    pub fn one<T: Prefetch>(&self, ctx: &Context<'_>) -> Result<Option<Option<T>>, Error> {
+        match self.get(ctx) {
+            None => Ok(None),
+            Some(Value::Null) => Ok(Some(None)),
+            Some(value) => {
+                let columns = projected_columns::<T>(ctx.field())?;
+                let object = json_object(value.clone())?;
+                Ok(Some(Some(T::from_json(object, &columns)?)))
+            }
+        }
+    }
+
+    fn get(&self, ctx: &Context<'_>) -> Option<&Value> {
+        let key = relation_key(&ctx.field());
+        self.0.as_ref().and_then(|o| o.get(key))
+    }
+}
+
+pub async fn query_prefetched<'c, T: Prefetch, F: InputFilter>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    order: Vec<OrderBy<'_>>,
+    page: PageArgs,
+) -> anyhow::Result<Vec<T>> {
+    let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+    let limit = page.limit(max_page_size);
+    let offset = page.offset();
+    let page = Page {
+        limit: &limit,
+        offset: offset.as_ref().map(|o| o as &(dyn ToSql + Sync)),
+    };
+
+    let mut tf = TableFilter::new(F::TABLE_NAME);
+    if let Some(filter) = filter.as_ref() {
+        filter.collect_into(tf.filter_group_mut());
+    }
+
+    let search = filter.as_ref().and_then(|f| f.text_search());
+    let order = search_order::<T>(search.is_some(), order)?;
+
+    let nested_limit = max_page_size as i64;
+    let projection = projection::<T>(ctx.field(), &nested_limit)?;
+
+    let mut visitor = SqlVisitor::new().order_by(order).paginate(page);
+    if let Some(search) = search {
+        visitor = visitor.rank(search);
+    }
+
+    let (sql, params) = visitor.translate_projection(&tf, &projection, VALUE_ALIAS);
+    tracing::debug!(
+        query=sql,
+        paramters=?params,
+        table=F::TABLE_NAME,
+    );
+
+    let db = ctx.data_unchecked::<Pool>();
+    let rows: Vec<JsonRow> = load(db, sql, params).await?;
+
+    rows.into_iter()
+        .map(|r| T::from_json(r.0, &projection.columns))
+        .collect()
+}
+
+pub fn projection<'s, T: Prefetch>(
+    field: SelectionField<'s>,
+    limit: &'s (dyn ToSql + Sync),
+) -> Result<Projection<'s>, Error> {
+    let columns = projected_columns::<T>(field)?;
+    let mut relations: Vec<ProjectedRelation> = vec![];
+    for child in field.selection_set() {
+        // fields with arguments are left to their resolvers
+        if !child.arguments().is_ok_and(|args| args.is_empty()) {
+            continue;
+        }
+
+        let key = relation_key(&child);
+        if relations.iter().any(|r| r.key == key) {
+            continue;
+        }
+
+        if let Some(relation) = T::relation(child, limit)? {
+            relations.push(relation);
+        }
+    }
+
+    Ok(Projection { columns, relations })
+}
+
+// The columns of the selected fields and the primary key
+fn projected_columns<T: TypeInfo>(field: SelectionField<'_>) -> Result<Vec<&'static str>, Error> {
+    let mut columns = selected_columns::<T>(field.selection_set())?;
+    if !columns.contains(&T::PRIMARY_KEY) {
+        columns.push(T::PRIMARY_KEY);
+    }
+
+    Ok(columns)
+}
+
+// The key of a relation in the prefetched entities
+pub fn relation_key<'s>(field: &SelectionField<'s>) -> &'s str {
+    field.alias().unwrap_or(field.name())
+}
+
+struct JsonRow(JsonObject);
+
+impl FromRow for JsonRow {
+    const COLUMNS: &'static [&'static str] = &[VALUE_ALIAS];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        match columns {
+            [column] => Ok(Self(json_object(row.try_get(column)?)?)),
+            _ => Err(anyhow!("expected a single column, found: {:?}", columns)),
+        }
+    }
+}
+
+fn json_object(value: Value) -> Result<JsonObject, Error> {
+    match value {
+        Value::Object(object) => Ok(object),
+        value => Err(anyhow!("expected a JSON object, found: {}", value)),
+    }
+}
+
+// Fails like `Row::try_get`, when the column was not fetched
+pub fn json_column(object: &mut JsonObject, column: &str) -> Result<Value, Error> {
+    object
+        .remove(column)
+        .ok_or_else(|| anyhow!("invalid column `{}`", column))
+}
+
+pub fn json_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    Ok(serde_json::from_value(value)?)
+}
+
+// The numerics are read as floats, so they are parsed from their shortest representation
+pub fn json_decimal(value: Value) -> Result<Decimal, Error> {
+    match value {
+        Value::Number(n) => Ok(Decimal::from_str(&n.to_string())?),
+        value => Err(anyhow!("expected a number, found: {}", value)),
+    }
+}
+
+pub fn json_timestamp(value: Value) -> Result<OffsetDateTime, Error> {
+    match value {
+        Value::String(s) => Ok(OffsetDateTime::parse(&s, &Rfc3339)?),
+        value => Err(anyhow!("expected a timestamp, found: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::model::{Film, Language};
+    use crate::query::RANK_COLUMN;
+    use serde_json::json;
+
+    fn object(value: Value) -> JsonObject {
+        json_object(value).unwrap()
+    }
+
+    #[test]
+    fn requested_columns_are_mapped() {
+        let language = Language::from_json(
+            object(json!({ "language_id": 1, "name": "English" })),
+            &["language_id", "name"],
+        )
+        .unwrap();
+
+        assert_eq!(language.language_id, 1);
+        assert_eq!(language.name, "English");
+    }
+
+    #[test]
+    fn missing_and_unmapped_columns_are_reported() {
+        let missing = Language::from_json(object(json!({ "language_id": 1 })), &["name"])
+            .err()
+            .unwrap();
+        assert_eq!(missing.to_string(), "invalid column `name`");
+
+        let unmapped = Language::from_json(object(json!({ "code": "en" })), &["code"])
+            .err()
+            .unwrap();
+        assert!(unmapped.to_string().contains("'code'"), "{}", unmapped);
+    }
+
+    #[test]
+    fn computed_columns_are_optional() {
+        let searched = object(json!({ "film_id": 1, RANK_COLUMN: 0.5 }));
+        let film = Film::from_json(searched, &["film_id"]).unwrap();
+        assert_eq!(film.rank, Some(0.5));
+
+        let plain = Film::from_json(object(json!({ "film_id": 1 })), &["film_id"]).unwrap();
+        assert_eq!(plain.rank, None);
+    }
+}
@@ -1,53 +1,275 @@
+use crate::config::QueryConfig;
 use crate::graphql::core::filter::{InputFilter, TypeInfo};
-use crate::graphql::core::loader::{load, FromRow};
-use crate::query::{JoinedTable, SqlVisitor, TableFilter};
-use async_graphql::Context;
+use crate::graphql::core::loader::{
+    load, load_columns, load_many_by_key_with_params, FilteredKey, FromRow, ListArgs, KEY_BULK_LOAD,
+};
+use crate::graphql::core::order::{sort_order, OrderInput};
+use crate::graphql::core::page::PageArgs;
+use crate::query::{
+    Direction, JoinedTable, OrderBy, OrderKey, Page, SqlVisitor, TableFilter, ValueFilter,
+    RANK_COLUMN,
+};
+use async_graphql::{Context, SelectionField};
 use deadpool_postgres::Pool;
+use rustc_hash::FxHashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
 use tokio_postgres::types::ToSql;
 
+// The alias of the single column of the queries loading a value per row, e.g.
+// the count loaders, the cursor probes and the JSON projections
+pub const VALUE_ALIAS: &str = "__value";
+
 pub async fn query<'c, 'f, T: TypeInfo + FromRow, F: InputFilter>(
     ctx: &Context<'c>,
     filter: &Option<F>,
     joined_table: Option<JoinedTable<'f>>,
+    order: Vec<OrderBy<'f>>,
+    page: PageArgs,
 ) -> anyhow::Result<Vec<T>> {
-    let selected = extract_selected_properties::<T>(ctx);
-    let (sql, params) = process(&filter, &selected, joined_table);
+    query_scoped(ctx, filter, joined_table, None, order, page).await
+}
+
+// Same as `query`, but only for the rows with the given column value, e.g. the
+// rentals referencing a customer
+pub async fn query_by_column<'c, 'f, T: TypeInfo + FromRow, F: InputFilter>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    column: ValueFilter<'f>,
+    order: Vec<OrderBy<'f>>,
+    page: PageArgs,
+) -> anyhow::Result<Vec<T>> {
+    query_scoped(ctx, filter, None, Some(column), order, page).await
+}
+
+async fn query_scoped<'c, 'f, T: TypeInfo + FromRow, F: InputFilter>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    joined_table: Option<JoinedTable<'f>>,
+    column: Option<ValueFilter<'f>>,
+    order: Vec<OrderBy<'f>>,
+    page: PageArgs,
+) -> anyhow::Result<Vec<T>> {
+    let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+    let limit = page.limit(max_page_size);
+    let offset = page.offset();
+    let page = Page {
+        limit: &limit,
+        offset: offset.as_ref().map(|o| o as &(dyn ToSql + Sync)),
+    };
+
+    let search = filter.as_ref().and_then(|f| f.text_search());
+    let order = search_order::<T>(search.is_some(), order)?;
+
+    let selected = extract_selected_properties::<T>(ctx)?;
+    let (sql, params) = process(&filter, &selected, joined_table, column, order, page);
+
+    // the rank of a text search is fetched along with the selected columns
+    let mut fetched = selected.clone();
+    if search.is_some() {
+        fetched.push(RANK_COLUMN);
+    }
 
     let db = ctx.data_unchecked::<Pool>();
-    let result = load(db, sql, params).await?;
+    let result = load_columns(db, sql, params, &fetched).await?;
+
+    Ok(result)
+}
+
+// Loads the rows of many parents, issuing one query per distinct list of
+// arguments. The order and the page are applied to the rows of each parent.
+pub async fn query_by_keys<T, F, O>(
+    db: &Pool,
+    keys: &[FilteredKey<F, O>],
+    join_table: &str,
+    join_table_join_col: &str,
+    data_table_join_col: &str,
+    join_table_filter_col: &str,
+) -> anyhow::Result<HashMap<FilteredKey<F, O>, Vec<T>>>
+where
+    T: TypeInfo + FromRow,
+    F: InputFilter,
+    O: OrderInput,
+{
+    let mut groups = FxHashMap::<Arc<ListArgs<F, O>>, Vec<i32>>::default();
+    for key in keys {
+        groups.entry(key.args.clone()).or_default().push(key.id);
+    }
+
+    let mut result = HashMap::new();
+    for (args, ids) in groups {
+        let mut tf = TableFilter::new(F::TABLE_NAME);
+        if let Some(filter) = args.filter.as_ref() {
+            filter.collect_into(tf.filter_group_mut());
+        }
+
+        let search = args.filter.as_ref().and_then(|f| f.text_search());
+        let order = sort_order::<T, O>(&args.order_by).map_err(|e| anyhow::anyhow!(e.message))?;
+        let mut order = search_order::<T>(search.is_some(), order)?;
+        if order.is_empty() {
+            order.push(OrderBy {
+                key: OrderKey::Column(T::PRIMARY_KEY),
+                direction: Direction::Asc,
+                nulls: None,
+            });
+        }
+
+        let joined_table = JoinedTable {
+            join_table,
+            join_table_join_col,
+            data_table_join_col,
+            join_table_filter_col,
+            join_table_filter_col_val: &ids,
+        };
+
+        let page = Page {
+            limit: &args.limit,
+            offset: args.offset.as_ref().map(|o| o as &(dyn ToSql + Sync)),
+        };
+
+        let mut visitor = SqlVisitor::with_joined_table(joined_table)
+            .loader_key(KEY_BULK_LOAD)
+            .order_by(order)
+            .paginate(page);
+        if let Some(search) = search {
+            visitor = visitor.rank(search);
+        }
+
+        let (sql, params) = visitor.translate(&tf, &[]);
+        tracing::debug!(
+            query=sql,
+            paramters=?params,
+            table=F::TABLE_NAME,
+        );
+
+        let mut rows = load_many_by_key_with_params::<T, i32>(db, &sql, params).await?;
+        for id in ids {
+            if let Some(values) = rows.remove(&id) {
+                let key = FilteredKey {
+                    id,
+                    args: args.clone(),
+                };
+                result.insert(key, values);
+            }
+        }
+    }
 
     Ok(result)
 }
 
-fn extract_selected_properties<'a, 's, T: TypeInfo>(ctx: &'s Context<'a>) -> Vec<&'s str> {
-    let mut columns = ctx
-        .field()
-        .selection_set()
-        .map(|s| s.name())
-        .filter_map(|s| T::QUERY_FIELD_TO_DB_COLUMN_MAP.get(&s).copied())
-        .collect::<Vec<_>>();
+// Counts the rows matching the filter, e.g. the films of an actor
+pub async fn query_count<'c, 'f, F: InputFilter>(
+    ctx: &Context<'c>,
+    filter: &Option<F>,
+    joined_table: Option<JoinedTable<'f>>,
+) -> anyhow::Result<i64> {
+    let mut tf = TableFilter::new(F::TABLE_NAME);
+    if let Some(filter) = filter.as_ref() {
+        filter.collect_into(tf.filter_group_mut());
+    }
+
+    let visitor = match joined_table {
+        None => SqlVisitor::new(),
+        Some(j) => SqlVisitor::with_joined_table(j),
+    };
+
+    let (sql, params) = visitor.translate_count(&tf, VALUE_ALIAS);
+    tracing::debug!(
+        query=sql,
+        paramters=?params,
+        table=F::TABLE_NAME,
+    );
+
+    let db = ctx.data_unchecked::<Pool>();
+    let count: Vec<i64> = load(db, sql, params).await?;
+
+    Ok(count.first().copied().unwrap_or_default())
+}
+
+pub fn search_order<'f, T: TypeInfo>(
+    search: bool,
+    order: Vec<OrderBy<'f>>,
+) -> anyhow::Result<Vec<OrderBy<'f>>> {
+    let order = match search {
+        // without an explicit order, the best matches of a text search come first
+        true if order.is_empty() => vec![
+            OrderBy {
+                key: OrderKey::Rank,
+                direction: Direction::Desc,
+                nulls: None,
+            },
+            OrderBy {
+                key: OrderKey::Column(T::PRIMARY_KEY),
+                direction: Direction::Asc,
+                nulls: None,
+            },
+        ],
+        true => order,
+        false if order.iter().any(|o| matches!(o.key, OrderKey::Rank)) => {
+            anyhow::bail!("ordering by relevance requires a search filter")
+        }
+        false => order,
+    };
+
+    Ok(order)
+}
+
+fn extract_selected_properties<'a, 's, T: TypeInfo>(
+    ctx: &'s Context<'a>,
+) -> anyhow::Result<Vec<&'s str>> {
+    selected_columns::<T>(ctx.field().selection_set())
+}
+
+// The columns backing the selected fields. A field without a column would be
+// resolved from a default value, so it is reported instead of being skipped.
+pub fn selected_columns<'s, T: TypeInfo>(
+    fields: impl Iterator<Item = SelectionField<'s>>,
+) -> anyhow::Result<Vec<&'static str>> {
+    let mut columns = vec![];
+    for field in fields {
+        let name = field.name();
+        match T::db_column(name) {
+            Some(column) => columns.push(column),
+            None if name == "__typename" || T::COMPUTED_FIELDS.contains(&name) => {}
+            None => anyhow::bail!(
+                "the field '{}' of '{}' is not mapped to a column",
+                name,
+                std::any::type_name::<T>()
+            ),
+        }
+    }
 
     columns.sort_unstable();
     columns.dedup();
-    columns
+    Ok(columns)
 }
 
 fn process<'l, F: InputFilter>(
     f: &'l Option<F>,
     s: &'l [&'l str],
     joined_table: Option<JoinedTable<'l>>,
+    column: Option<ValueFilter<'l>>,
+    order: Vec<OrderBy<'l>>,
+    page: Page<'l>,
 ) -> (String, Vec<&'l (dyn ToSql + Sync)>) {
     let mut tf = TableFilter::new(F::TABLE_NAME);
+    if let Some(column) = column {
+        tf.filter_group_mut().add_filter(column);
+    }
     if let Some(filter) = f.as_ref() {
         filter.collect_into(tf.filter_group_mut());
     }
 
-    let visitor = match joined_table {
+    let mut visitor = match joined_table {
         None => SqlVisitor::new(),
         Some(j) => SqlVisitor::with_joined_table(j),
     };
 
-    let (sql, params) = visitor.translate(&tf, s);
+    if let Some(search) = f.as_ref().and_then(|f| f.text_search()) {
+        visitor = visitor.rank(search);
+    }
+
+    let (sql, params) = visitor.order_by(order).paginate(page).translate(&tf, s);
     tracing::debug!(
         query=sql,
         paramters=?params,
@@ -56,3 +278,75 @@ fn process<'l, F: InputFilter>(
 
     (sql, params)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+    use std::sync::{LazyLock, Mutex};
+
+    #[derive(Default, SimpleObject)]
+    struct Item {
+        id: i32,
+        name: String,
+        kind: String,
+    }
+
+    impl TypeInfo for Item {
+        const PRIMARY_KEY: &'static str = "item_id";
+        const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+            LazyLock::new(|| {
+                let mut map = FxHashMap::default();
+                map.insert("id", "item_id");
+                map.insert("name", "item_name");
+                map
+            });
+    }
+
+    type Selected = Arc<Mutex<Option<anyhow::Result<Vec<&'static str>>>>>;
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn items(&self, ctx: &Context<'_>) -> Vec<Item> {
+            let columns = selected_columns::<Item>(ctx.field().selection_set());
+            *ctx.data_unchecked::<Selected>().lock().unwrap() = Some(columns);
+            vec![]
+        }
+    }
+
+    async fn selected(query: &str) -> anyhow::Result<Vec<&'static str>> {
+        let selected = Selected::default();
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .data(selected.clone())
+            .finish();
+
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let columns = selected.lock().unwrap().take();
+        columns.expect("the items are resolved")
+    }
+
+    #[tokio::test]
+    async fn fragments_and_aliases_select_the_columns_of_the_fields() {
+        let columns = selected(
+            "query { items { ...named ... on Item { label: name } key: id __typename } }
+            fragment named on Item { name }",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(columns, ["item_id", "item_name"]);
+    }
+
+    #[tokio::test]
+    async fn unmapped_fields_are_reported() {
+        let error = selected("{ items { id ... on Item { kind } } }")
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("'kind'"), "{}", error);
+    }
+}
@@ -1,9 +1,9 @@
-use crate::graphql::core::loader::load_one_by_key;
-use crate::graphql::model::Actor;
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::{Actor, ActorFilter};
 use async_graphql::dataloader::Loader;
 use deadpool_postgres::Pool;
 use std::collections::HashMap;
-use std::future::Future;
 use std::sync::Arc;
 
 pub struct ActorLoader {
@@ -16,22 +16,16 @@ impl ActorLoader {
     }
 }
 
-impl Loader<i32> for ActorLoader {
+impl Loader<ColumnsKey> for ActorLoader {
     type Value = Actor;
     type Error = Arc<anyhow::Error>;
 
-    fn load(
+    async fn load(
         &self,
-        keys: &[i32],
-    ) -> impl Future<Output = Result<HashMap<i32, Self::Value>, Self::Error>> + Send {
-        async {
-            load_one_by_key(
-                &self.db,
-                "SELECT actor_id, first_name, last_name FROM actor WHERE actor_id = ANY($1)",
-                keys,
-            )
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, ActorFilter::TABLE_NAME, keys)
             .await
-            .map_err(|e| Arc::new(e))
-        }
+            .map_err(Arc::new)
     }
 }
@@ -0,0 +1,30 @@
+use crate::graphql::core::loader::load_value_by_key;
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct ActorFilmCountLoader {
+    db: Pool,
+}
+
+impl ActorFilmCountLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i32> for ActorFilmCountLoader {
+    type Value = i64;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        load_value_by_key(
+            &self.db,
+            "SELECT actor_id as __loader_key, count(*) as __value FROM film_actor WHERE actor_id = ANY($1) GROUP BY actor_id",
+            keys,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
@@ -0,0 +1,39 @@
+use crate::graphql::core::loader::FilteredKey;
+use crate::graphql::core::query::query_by_keys;
+use crate::graphql::model::join_tables::{JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__FILM_ID};
+use crate::graphql::model::{Actor, Film, FilmFilter, FilmOrder};
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct ActorFilmFilteredLoader {
+    db: Pool,
+}
+
+impl ActorFilmFilteredLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<FilteredKey<FilmFilter, FilmOrder>> for ActorFilmFilteredLoader {
+    type Value = Vec<Film>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[FilteredKey<FilmFilter, FilmOrder>],
+    ) -> Result<HashMap<FilteredKey<FilmFilter, FilmOrder>, Self::Value>, Self::Error> {
+        query_by_keys(
+            &self.db,
+            keys,
+            JOIN_TABLE__FILM_ACTOR,
+            JOIN_TABLE__FILM_ACTOR__FILM_ID,
+            Film::COLUMN_FILM_ID,
+            Actor::COLUMN_ACTOR_ID,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
@@ -26,7 +26,7 @@ impl Loader<i32> for ActorFilmIdLoader {
         async {
             load_many_by_key(
                 &self.db,
-                "SELECT actor_id as __loader_key, film_id as __value FROM film_actor WHERE actor_id = ANY($1)",
+                "SELECT actor_id as __loader_key, film_id as __value FROM film_actor WHERE actor_id = ANY($1) ORDER BY film_id",
                 keys,
             )
             .await
@@ -1,9 +1,9 @@
-use crate::graphql::core::loader::load_one_by_key;
-use crate::graphql::model::Category;
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::{Category, CategoryFilter};
 use async_graphql::dataloader::Loader;
 use deadpool_postgres::Pool;
 use std::collections::HashMap;
-use std::future::Future;
 use std::sync::Arc;
 
 pub struct CategoryLoader {
@@ -16,20 +16,16 @@ impl CategoryLoader {
     }
 }
 
-impl Loader<i32> for CategoryLoader {
+impl Loader<ColumnsKey> for CategoryLoader {
     type Value = Category;
     type Error = Arc<anyhow::Error>;
 
-    fn load(
+    async fn load(
         &self,
-        keys: &[i32],
-    ) -> impl Future<Output = Result<HashMap<i32, Self::Value>, Self::Error>> + Send {
-        static QUERY: &str = "SELECT category_id, name FROM category where category_id = ANY($1)";
-
-        async {
-            load_one_by_key(&self.db, &QUERY, keys)
-                .await
-                .map_err(|e| Arc::new(e))
-        }
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, CategoryFilter::TABLE_NAME, keys)
+            .await
+            .map_err(Arc::new)
     }
 }
@@ -0,0 +1,31 @@
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::{City, CityFilter};
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct CityLoader {
+    db: Pool,
+}
+
+impl CityLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<ColumnsKey> for CityLoader {
+    type Value = City;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, CityFilter::TABLE_NAME, keys)
+            .await
+            .map_err(Arc::new)
+    }
+}
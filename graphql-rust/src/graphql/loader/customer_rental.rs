@@ -0,0 +1,31 @@
+use crate::graphql::core::loader::load_many_by_key;
+use crate::graphql::model::Rental;
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct CustomerRentalLoader {
+    db: Pool,
+}
+
+impl CustomerRentalLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i32> for CustomerRentalLoader {
+    type Value = Vec<Rental>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        load_many_by_key(
+            &self.db,
+            "SELECT customer_id as __loader_key, rental_id, rental_date, inventory_id, customer_id, return_date, staff_id, last_update FROM rental WHERE customer_id = ANY($1) ORDER BY rental_id",
+            keys,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
@@ -1,9 +1,9 @@
-use crate::graphql::core::loader::load_one_by_key;
-use crate::graphql::model::Film;
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::{Film, FilmFilter};
 use async_graphql::dataloader::Loader;
 use deadpool_postgres::Pool;
 use std::collections::HashMap;
-use std::future::Future;
 use std::sync::Arc;
 
 pub struct FilmLoader {
@@ -16,18 +16,16 @@ impl FilmLoader {
     }
 }
 
-impl Loader<i32> for FilmLoader {
+impl Loader<ColumnsKey> for FilmLoader {
     type Value = Film;
     type Error = Arc<anyhow::Error>;
 
-    fn load(
+    async fn load(
         &self,
-        keys: &[i32],
-    ) -> impl Future<Output = Result<HashMap<i32, Self::Value>, Self::Error>> + Send {
-        async {
-            load_one_by_key(&self.db, "SELECT film_id, title, description, length, language_id, original_language_id FROM film WHERE film_id = ANY($1)", keys)
-                .await
-                .map_err(|e| Arc::new(e))
-        }
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, FilmFilter::TABLE_NAME, keys)
+            .await
+            .map_err(Arc::new)
     }
 }
@@ -0,0 +1,41 @@
+use crate::graphql::core::loader::FilteredKey;
+use crate::graphql::core::query::query_by_keys;
+use crate::graphql::model::join_tables::{
+    JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+};
+use crate::graphql::model::{Actor, ActorFilter, ActorOrder, Film};
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct FilmActorFilteredLoader {
+    db: Pool,
+}
+
+impl FilmActorFilteredLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<FilteredKey<ActorFilter, ActorOrder>> for FilmActorFilteredLoader {
+    type Value = Vec<Actor>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[FilteredKey<ActorFilter, ActorOrder>],
+    ) -> Result<HashMap<FilteredKey<ActorFilter, ActorOrder>, Self::Value>, Self::Error> {
+        query_by_keys(
+            &self.db,
+            keys,
+            JOIN_TABLE__FILM_ACTOR,
+            JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+            Actor::COLUMN_ACTOR_ID,
+            Film::COLUMN_FILM_ID,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
@@ -26,7 +26,7 @@ impl Loader<i32> for FilmActorIdLoader {
         async {
             load_many_by_key(
                 &self.db,
-                "SELECT film_id as __loader_key, actor_id as __value FROM film_actor WHERE film_id = ANY($1)",
+                "SELECT film_id as __loader_key, actor_id as __value FROM film_actor WHERE film_id = ANY($1) ORDER BY actor_id",
                 keys,
             )
             .await
@@ -26,7 +26,7 @@ impl Loader<i32> for FilmCategoryIdLoader {
         async {
             load_many_by_key(
                 &self.db,
-                "SELECT film_id as __loader_key, category_id as __value FROM film_category WHERE film_id = ANY($1)",
+                "SELECT film_id as __loader_key, category_id as __value FROM film_category WHERE film_id = ANY($1) ORDER BY category_id",
                 keys,
             )
             .await
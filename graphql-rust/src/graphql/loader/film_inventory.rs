@@ -0,0 +1,31 @@
+use crate::graphql::core::loader::load_many_by_key;
+use crate::graphql::model::Inventory;
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct FilmInventoryLoader {
+    db: Pool,
+}
+
+impl FilmInventoryLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i32> for FilmInventoryLoader {
+    type Value = Vec<Inventory>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        load_many_by_key(
+            &self.db,
+            "SELECT film_id as __loader_key, inventory_id, film_id, store_id FROM inventory WHERE film_id = ANY($1) ORDER BY inventory_id",
+            keys,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
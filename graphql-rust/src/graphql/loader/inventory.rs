@@ -0,0 +1,30 @@
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::Inventory;
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct InventoryLoader {
+    db: Pool,
+}
+
+impl InventoryLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<ColumnsKey> for InventoryLoader {
+    type Value = Inventory;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, Inventory::TABLE_NAME, keys)
+            .await
+            .map_err(Arc::new)
+    }
+}
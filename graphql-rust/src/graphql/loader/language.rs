@@ -1,9 +1,9 @@
-use crate::graphql::core::loader::load_one_by_key;
-use crate::graphql::model::Language;
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::{load_columns_by_key, ColumnsKey};
+use crate::graphql::model::{Language, LanguageFilter};
 use async_graphql::dataloader::Loader;
 use deadpool_postgres::Pool;
 use std::collections::HashMap;
-use std::future::Future;
 use std::sync::Arc;
 
 pub struct LanguageLoader {
@@ -16,22 +16,16 @@ impl LanguageLoader {
     }
 }
 
-impl Loader<i32> for LanguageLoader {
+impl Loader<ColumnsKey> for LanguageLoader {
     type Value = Language;
     type Error = Arc<anyhow::Error>;
 
-    fn load(
+    async fn load(
         &self,
-        keys: &[i32],
-    ) -> impl Future<Output = Result<HashMap<i32, Self::Value>, Self::Error>> + Send {
-        async {
-            load_one_by_key(
-                &self.db,
-                "SELECT language_id, name FROM language WHERE language_id = ANY($1)",
-                keys,
-            )
+        keys: &[ColumnsKey],
+    ) -> Result<HashMap<ColumnsKey, Self::Value>, Self::Error> {
+        load_columns_by_key(&self.db, LanguageFilter::TABLE_NAME, keys)
             .await
-            .map_err(|e| Arc::new(e))
-        }
+            .map_err(Arc::new)
     }
 }
@@ -1,15 +1,41 @@
 mod actor;
+mod actor_film_count;
+mod actor_film_filtered;
 mod actor_film_id;
+mod address;
 mod category;
+mod city;
+mod country;
+mod customer_rental;
 mod film;
+mod film_actor_count;
+mod film_actor_filtered;
 mod film_actor_id;
 mod film_category_id;
+mod film_inventory;
+mod inventory;
 mod language;
+mod rental_payment;
+mod staff;
+mod store;
 
 pub use actor::ActorLoader;
+pub use actor_film_count::ActorFilmCountLoader;
+pub use actor_film_filtered::ActorFilmFilteredLoader;
 pub use actor_film_id::ActorFilmIdLoader;
+pub use address::AddressLoader;
 pub use category::CategoryLoader;
+pub use city::CityLoader;
+pub use country::CountryLoader;
+pub use customer_rental::CustomerRentalLoader;
 pub use film::FilmLoader;
+pub use film_actor_count::FilmActorCountLoader;
+pub use film_actor_filtered::FilmActorFilteredLoader;
 pub use film_actor_id::FilmActorIdLoader;
 pub use film_category_id::FilmCategoryIdLoader;
+pub use film_inventory::FilmInventoryLoader;
+pub use inventory::InventoryLoader;
 pub use language::LanguageLoader;
+pub use rental_payment::RentalPaymentLoader;
+pub use staff::StaffLoader;
+pub use store::StoreLoader;
@@ -0,0 +1,31 @@
+use crate::graphql::core::loader::load_many_by_key;
+use crate::graphql::model::Payment;
+use async_graphql::dataloader::Loader;
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct RentalPaymentLoader {
+    db: Pool,
+}
+
+impl RentalPaymentLoader {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i32> for RentalPaymentLoader {
+    type Value = Vec<Payment>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        load_many_by_key(
+            &self.db,
+            "SELECT rental_id as __loader_key, payment_id, customer_id, staff_id, rental_id, amount, payment_date FROM payment WHERE rental_id = ANY($1) ORDER BY payment_id",
+            keys,
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
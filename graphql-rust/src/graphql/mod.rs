@@ -1,15 +1,28 @@
+use crate::config::{ExecutionMode, QueryConfig};
+use crate::graphql::core::aggregate::aggregate;
+use crate::graphql::core::connection::{connection, EntityConnection};
+use crate::graphql::core::order::sort_order;
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::prefetch::query_prefetched;
 use crate::graphql::core::query::query;
-use crate::graphql::model::{Actor, ActorFilter, Film, FilmFilter};
+use crate::graphql::model::{
+    Actor, ActorFilter, ActorOrder, Customer, CustomerFilter, CustomerOrder, Film, FilmAggregate,
+    FilmFilter, FilmGroupBy, FilmOrder, Payment, PaymentFilter, PaymentOrder, Rental, RentalFilter,
+    RentalOrder,
+};
+use crate::graphql::mutation::Mutation;
 use crate::server::AppState;
-use async_graphql::{Context, EmptyMutation, EmptySubscription, Executor, Object, Schema};
+use async_graphql::{Context, EmptySubscription, Executor, Object, Schema};
 
 mod core;
 pub mod loader;
 mod model;
+mod mutation;
 
-pub fn build_schema(state: AppState) -> impl Executor {
-    Schema::build(Query, EmptyMutation, EmptySubscription)
+pub fn build_schema(state: AppState, config: QueryConfig) -> impl Executor {
+    Schema::build(Query, Mutation::default(), EmptySubscription)
         .data(state.db.db.clone())
+        .data(config)
         .finish()
 }
 
@@ -21,15 +34,106 @@ impl Query {
         &self,
         ctx: &Context<'a>,
         filter: Option<ActorFilter>,
+        order_by: Option<Vec<ActorOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
     ) -> async_graphql::Result<Vec<Actor>> {
-        Ok(query(ctx, &filter, None).await?)
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Actor, _>(&order_by)?;
+
+        match ctx.data_unchecked::<QueryConfig>().execution {
+            ExecutionMode::DataLoader => Ok(query(ctx, &filter, None, order, page).await?),
+            ExecutionMode::SingleQuery => Ok(query_prefetched(ctx, &filter, order, page).await?),
+        }
     }
 
     async fn films<'a>(
         &self,
         ctx: &Context<'a>,
         filter: Option<FilmFilter>,
+        order_by: Option<Vec<FilmOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
     ) -> async_graphql::Result<Vec<Film>> {
-        Ok(query(ctx, &filter, None).await?)
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Film, _>(&order_by)?;
+
+        match ctx.data_unchecked::<QueryConfig>().execution {
+            ExecutionMode::DataLoader => Ok(query(ctx, &filter, None, order, page).await?),
+            ExecutionMode::SingleQuery => Ok(query_prefetched(ctx, &filter, order, page).await?),
+        }
+    }
+
+    async fn films_aggregate<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<FilmFilter>,
+        group_by: Option<Vec<FilmGroupBy>>,
+    ) -> async_graphql::Result<Vec<FilmAggregate>> {
+        let requested = FilmAggregate::requested(ctx, &group_by);
+        Ok(aggregate(ctx, &filter, &requested).await?)
+    }
+
+    async fn customers<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<CustomerFilter>,
+        order_by: Option<Vec<CustomerOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<Customer>> {
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Customer, _>(&order_by)?;
+        Ok(query(ctx, &filter, None, order, page).await?)
+    }
+
+    async fn rentals<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<RentalFilter>,
+        order_by: Option<Vec<RentalOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<Rental>> {
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Rental, _>(&order_by)?;
+        Ok(query(ctx, &filter, None, order, page).await?)
+    }
+
+    async fn payments<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<PaymentFilter>,
+        order_by: Option<Vec<PaymentOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<Payment>> {
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Payment, _>(&order_by)?;
+        Ok(query(ctx, &filter, None, order, page).await?)
+    }
+
+    async fn actors_connection<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<ActorFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<EntityConnection<Actor>> {
+        connection(ctx, &filter, after, before, first, last).await
+    }
+
+    async fn films_connection<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<FilmFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<EntityConnection<Film>> {
+        connection(ctx, &filter, after, before, first, last).await
     }
 }
@@ -1,44 +1,76 @@
-use crate::graphql::core::filter::{
-    and_filters, eq, is_in, is_not_in, join_table, neq, or_filters, InputFilter, TypeInfo,
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::graphql::core::loader::{ColumnsKey, FilteredKey};
+use crate::graphql::core::order::{
+    join_table_count_key, sort_order, NullsOrder, OrderDirection, OrderInput, SortKey,
+};
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::prefetch::{
+    json_timestamp, projection, relation_key, Prefetch, Prefetched,
+};
+use crate::graphql::core::query::query_count;
+use crate::graphql::loader::{
+    ActorFilmCountLoader, ActorFilmFilteredLoader, ActorFilmIdLoader, FilmLoader,
 };
-use crate::graphql::core::loader::{FromRow, WithId};
-use crate::graphql::core::query::query;
-use crate::graphql::loader::{ActorFilmIdLoader, FilmLoader};
 use crate::graphql::model::join_tables::{
     JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID, JOIN_TABLE__FILM_ACTOR__FILM_ID,
 };
-use crate::graphql::model::{Film, FilmFilter};
-use crate::query::{Combinator, FilterGroup, JoinedTable};
-use crate::util::MaybeOwned;
+use crate::graphql::model::{Film, FilmFilter, FilmOrder};
+use crate::query::{JoinedTable, ProjectedRelation, RelationJoin};
 use anyhow::Error;
 use async_graphql::dataloader::{DataLoader, HashMapCache};
-use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
-use rustc_hash::{FxBuildHasher, FxHashMap};
-use std::sync::LazyLock;
-use tokio_postgres::Row;
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SelectionField, SimpleObject};
+use rustc_hash::FxBuildHasher;
+use sakila_derive::{SakilaEntity, SakilaFilter};
+use time::OffsetDateTime;
+use tokio_postgres::types::ToSql;
 
-#[derive(Debug, Clone, Default, SimpleObject)]
+#[derive(Debug, Clone, SimpleObject, SakilaEntity)]
 #[graphql(complex)]
 pub struct Actor {
-    #[graphql(skip)]
+    #[sakila(primary_key, resolves("films", "filmsCount"))]
     pub actor_id: i32,
 
     pub first_name: String,
     pub last_name: String,
+    #[sakila(json = json_timestamp)]
+    pub last_update: OffsetDateTime,
+
+    #[graphql(skip)]
+    #[sakila(prefetched)]
+    pub prefetched: Prefetched,
+}
+
+impl Default for Actor {
+    fn default() -> Self {
+        Self {
+            actor_id: 0,
+            first_name: String::new(),
+            last_name: String::new(),
+            last_update: OffsetDateTime::UNIX_EPOCH,
+            prefetched: Prefetched::default(),
+        }
+    }
 }
 
 #[ComplexObject]
 impl Actor {
-    pub const COLUMN_FIRST_NAME: &'static str = "first_name";
-    pub const COLUMN_LAST_NAME: &'static str = "last_name";
-    pub const COLUMN_ACTOR_ID: &'static str = "actor_id";
-
     async fn films<'a>(
         &self,
         ctx: &Context<'a>,
         filter: Option<FilmFilter>,
+        order_by: Option<Vec<FilmOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
     ) -> async_graphql::Result<Vec<Film>> {
-        if filter.is_none() {
+        if let Some(films) = self.prefetched.many::<Film>(ctx)? {
+            return Ok(films);
+        }
+
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Film, _>(&order_by)?;
+
+        if filter.is_none() && order.is_empty() && page.is_empty() {
             let ids = ctx
                 .data_unchecked::<DataLoader<ActorFilmIdLoader, HashMapCache<FxBuildHasher>>>()
                 .load_one(self.actor_id)
@@ -48,12 +80,47 @@ impl Actor {
                 return Ok(vec![]);
             };
 
-            let films = ctx
+            let keys = ColumnsKey::many::<Film>(ctx, &ids)?;
+            let mut films = ctx
                 .data_unchecked::<DataLoader<FilmLoader, HashMapCache<FxBuildHasher>>>()
-                .load_many(ids)
+                .load_many(keys.iter().cloned())
                 .await?;
 
-            return Ok(films.into_values().collect());
+            let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+            return Ok(keys
+                .iter()
+                .filter_map(|key| films.remove(key))
+                .take(max_page_size as usize)
+                .collect());
+        }
+
+        let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+        let films = ctx
+            .data_unchecked::<DataLoader<ActorFilmFilteredLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(FilteredKey::new(
+                self.actor_id,
+                filter,
+                order_by,
+                page,
+                max_page_size,
+            ))
+            .await?;
+
+        Ok(films.unwrap_or_default())
+    }
+
+    async fn films_count<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<FilmFilter>,
+    ) -> async_graphql::Result<i64> {
+        if filter.is_none() {
+            let count = ctx
+                .data_unchecked::<DataLoader<ActorFilmCountLoader, HashMapCache<FxBuildHasher>>>()
+                .load_one(self.actor_id)
+                .await?;
+
+            return Ok(count.unwrap_or_default());
         }
 
         let joined_table = JoinedTable {
@@ -64,58 +131,79 @@ impl Actor {
             join_table_filter_col_val: &self.actor_id,
         };
 
-        Ok(query(ctx, &filter, Some(joined_table)).await?)
-    }
-}
-
-impl WithId<i32> for Actor {
-    fn id(&self) -> &i32 {
-        &self.actor_id
+        Ok(query_count(ctx, &filter, Some(joined_table)).await?)
     }
 }
 
-impl TypeInfo for Actor {
-    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
-        LazyLock::new(|| {
-            let mut map = FxHashMap::default();
-            map.insert("firstName", Self::COLUMN_FIRST_NAME);
-            map.insert("lastName", Self::COLUMN_LAST_NAME);
-            map.insert("films", Self::COLUMN_ACTOR_ID);
-            map
-        });
-}
-
-impl FromRow for Actor {
-    fn from_row(row: &Row) -> Result<Self, Error>
-    where
-        Self: Sized,
-    {
-        let mut result = Self::default();
-        for col in row.columns() {
-            match col.name() {
-                Self::COLUMN_ACTOR_ID => result.actor_id = row.try_get(Self::COLUMN_ACTOR_ID)?,
-                Self::COLUMN_FIRST_NAME => {
-                    result.first_name = row.try_get(Self::COLUMN_FIRST_NAME)?
-                }
-                Self::COLUMN_LAST_NAME => result.last_name = row.try_get(Self::COLUMN_LAST_NAME)?,
-                col => tracing::debug!(
-                    "fetched unknown column '{}' for type '{}'",
-                    col,
-                    std::any::type_name::<Self>()
-                ),
-            }
-        }
+impl Prefetch for Actor {
+    fn relation<'s>(
+        field: SelectionField<'s>,
+        limit: &'s (dyn ToSql + Sync),
+    ) -> Result<Option<ProjectedRelation<'s>>, Error> {
+        let relation = match field.name() {
+            "films" => ProjectedRelation {
+                key: relation_key(&field),
+                table: FilmFilter::TABLE_NAME,
+                join: RelationJoin::ToMany {
+                    parent_column: Self::COLUMN_ACTOR_ID,
+                    join_table: JOIN_TABLE__FILM_ACTOR,
+                    join_table_parent_col: JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+                    join_table_child_col: JOIN_TABLE__FILM_ACTOR__FILM_ID,
+                    child_column: Film::COLUMN_FILM_ID,
+                    primary_key: Film::PRIMARY_KEY,
+                    limit,
+                },
+                projection: projection::<Film>(field, limit)?,
+            },
+            _ => return Ok(None),
+        };
 
-        Ok(result)
+        Ok(Some(relation))
     }
 }
 
-#[derive(Debug, Clone, Default, InputObject)]
+#[derive(Debug, Clone, Default, InputObject, SakilaFilter)]
+#[sakila(
+    table = "actor",
+    entity = Actor,
+    join_table(
+        name = "films",
+        table = JOIN_TABLE__FILM_ACTOR,
+        table_parent_col = JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+        table_child_col = JOIN_TABLE__FILM_ACTOR__FILM_ID,
+        parent_col = Actor::COLUMN_ACTOR_ID,
+        child_col = Film::COLUMN_FILM_ID,
+    )
+)]
 pub struct ActorFilter {
+    #[sakila(and)]
     pub and: Option<Vec<ActorFilter>>,
+    #[sakila(or)]
     pub or: Option<Vec<ActorFilter>>,
+    #[sakila(not)]
+    pub not: Option<Box<ActorFilter>>,
 
+    #[sakila(join_table = "films")]
     pub film: Option<Box<FilmFilter>>,
+    #[sakila(join_table = "films")]
+    pub films_some: Option<Box<FilmFilter>>,
+    #[sakila(join_table = "films", every)]
+    pub films_every: Option<Box<FilmFilter>>,
+    #[sakila(join_table = "films", none)]
+    pub films_none: Option<Box<FilmFilter>>,
+
+    #[sakila(count_filter = "films")]
+    pub film_count_filter: Option<Box<FilmFilter>>,
+    #[sakila(count = "films")]
+    pub film_count_eq: Option<i64>,
+    #[sakila(count = "films")]
+    pub film_count_gt: Option<i64>,
+    #[sakila(count = "films")]
+    pub film_count_gte: Option<i64>,
+    #[sakila(count = "films")]
+    pub film_count_lt: Option<i64>,
+    #[sakila(count = "films")]
+    pub film_count_lte: Option<i64>,
 
     pub first_name_eq: Option<String>,
     pub first_name_in: Option<Vec<String>>,
@@ -123,63 +211,60 @@ pub struct ActorFilter {
     pub first_name_not_eq: Option<String>,
     pub first_name_not_in: Option<Vec<String>>,
 
+    pub first_name_contains: Option<String>,
+    pub first_name_i_contains: Option<String>,
+    pub first_name_starts_with: Option<String>,
+    pub first_name_ends_with: Option<String>,
+    pub first_name_ilike: Option<String>,
+    pub first_name_regex: Option<String>,
+
     pub last_name_eq: Option<String>,
     pub last_name_in: Option<Vec<String>>,
 
     pub last_name_not_eq: Option<String>,
     pub last_name_not_in: Option<Vec<String>>,
+
+    pub last_name_contains: Option<String>,
+    pub last_name_i_contains: Option<String>,
+    pub last_name_starts_with: Option<String>,
+    pub last_name_ends_with: Option<String>,
+    pub last_name_ilike: Option<String>,
+    pub last_name_regex: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum ActorOrderField {
+    FirstName,
+    LastName,
+    FilmCount,
 }
 
-impl InputFilter for ActorFilter {
-    const TABLE_NAME: &'static str = "actor";
+#[derive(Debug, Clone, InputObject)]
+pub struct ActorOrder {
+    pub field: ActorOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
 
-    fn or_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        self.or.as_ref().map(|x| x.as_slice())
+impl OrderInput for ActorOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            ActorOrderField::FirstName => SortKey::Field("firstName"),
+            ActorOrderField::LastName => SortKey::Field("lastName"),
+            ActorOrderField::FilmCount => join_table_count_key(
+                JOIN_TABLE__FILM_ACTOR,
+                JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+                Actor::COLUMN_ACTOR_ID,
+            ),
+        }
     }
 
-    fn and_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        self.and.as_ref().map(|x| x.as_slice())
+    fn direction(&self) -> OrderDirection {
+        self.direction
     }
 
-    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
-        let mut g = match collector.combinator() == Combinator::And {
-            true => MaybeOwned::Borrowed(collector),
-            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
-        };
-
-        and_filters(&mut g, self);
-        or_filters(&mut g, self);
-
-        eq(&mut g, Actor::COLUMN_FIRST_NAME, &self.first_name_eq);
-        neq(&mut g, Actor::COLUMN_FIRST_NAME, &self.first_name_not_eq);
-
-        is_in(&mut g, Actor::COLUMN_FIRST_NAME, &self.first_name_in);
-        is_not_in(&mut g, Actor::COLUMN_FIRST_NAME, &self.first_name_not_in);
-
-        eq(&mut g, Actor::COLUMN_LAST_NAME, &self.last_name_eq);
-        neq(&mut g, Actor::COLUMN_LAST_NAME, &self.last_name_not_eq);
-
-        is_in(&mut g, Actor::COLUMN_LAST_NAME, &self.last_name_in);
-        is_not_in(&mut g, Actor::COLUMN_LAST_NAME, &self.last_name_not_in);
-
-        join_table(
-            &mut g,
-            &self.film,
-            JOIN_TABLE__FILM_ACTOR,
-            JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
-            JOIN_TABLE__FILM_ACTOR__FILM_ID,
-            Actor::COLUMN_ACTOR_ID,
-            Film::COLUMN_FILM_ID,
-        );
-
-        if let MaybeOwned::Owned(g) = g {
-            collector.add_group(g);
-        }
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
     }
 }
@@ -0,0 +1,191 @@
+use crate::graphql::core::filter::{
+    and_filters, contains, eq, is_in, is_not_in, join_column, neq, not_filter, or_filters,
+    InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::loader::CityLoader;
+use crate::graphql::model::{City, CityFilter};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct Address {
+    #[graphql(skip)]
+    pub address_id: i32,
+
+    #[graphql(skip)]
+    pub city_id: i32,
+
+    pub address: String,
+    pub address2: Option<String>,
+    pub district: String,
+    pub postal_code: Option<String>,
+    pub phone: String,
+}
+
+#[ComplexObject]
+impl Address {
+    pub const COLUMN_ADDRESS: &'static str = "address";
+    pub const COLUMN_ADDRESS2: &'static str = "address2";
+    pub const COLUMN_DISTRICT: &'static str = "district";
+    pub const COLUMN_POSTAL_CODE: &'static str = "postal_code";
+    pub const COLUMN_PHONE: &'static str = "phone";
+    pub const COLUMN_ADDRESS_ID: &'static str = "address_id";
+    pub const COLUMN_CITY_ID: &'static str = "city_id";
+
+    async fn city<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<City> {
+        let city = ctx
+            .data_unchecked::<DataLoader<CityLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<City>(ctx, self.city_id)?)
+            .await?;
+
+        referenced(city, "city", self.city_id)
+    }
+}
+
+impl TypeInfo for Address {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_ADDRESS_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("address", Self::COLUMN_ADDRESS);
+            map.insert("address2", Self::COLUMN_ADDRESS2);
+            map.insert("district", Self::COLUMN_DISTRICT);
+            map.insert("postalCode", Self::COLUMN_POSTAL_CODE);
+            map.insert("phone", Self::COLUMN_PHONE);
+            map.insert("city", Self::COLUMN_CITY_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Address {
+    fn id(&self) -> &i32 {
+        &self.address_id
+    }
+}
+
+impl FromRow for Address {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_ADDRESS,
+        Self::COLUMN_DISTRICT,
+        Self::COLUMN_POSTAL_CODE,
+        Self::COLUMN_PHONE,
+        Self::COLUMN_ADDRESS_ID,
+        Self::COLUMN_CITY_ID,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_ADDRESS => result.address = row.try_get(Self::COLUMN_ADDRESS)?,
+                Self::COLUMN_ADDRESS2 => result.address2 = row.try_get(Self::COLUMN_ADDRESS2)?,
+                Self::COLUMN_DISTRICT => result.district = row.try_get(Self::COLUMN_DISTRICT)?,
+                Self::COLUMN_POSTAL_CODE => {
+                    result.postal_code = row.try_get(Self::COLUMN_POSTAL_CODE)?
+                }
+                Self::COLUMN_PHONE => result.phone = row.try_get(Self::COLUMN_PHONE)?,
+                Self::COLUMN_ADDRESS_ID => {
+                    result.address_id = row.try_get(Self::COLUMN_ADDRESS_ID)?
+                }
+                Self::COLUMN_CITY_ID => result.city_id = row.try_get(Self::COLUMN_CITY_ID)?,
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct AddressFilter {
+    pub city: Option<Box<CityFilter>>,
+
+    pub address_eq: Option<String>,
+    pub address_contains: Option<String>,
+
+    pub district_eq: Option<String>,
+    pub district_in: Option<Vec<String>>,
+    pub district_not_eq: Option<String>,
+    pub district_not_in: Option<Vec<String>>,
+
+    pub postal_code_eq: Option<String>,
+    pub postal_code_in: Option<Vec<String>>,
+
+    pub phone_eq: Option<String>,
+}
+
+impl InputFilter for AddressFilter {
+    const TABLE_NAME: &'static str = "address";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, Address::COLUMN_ADDRESS, &self.address_eq);
+        contains(&mut g, Address::COLUMN_ADDRESS, &self.address_contains);
+
+        eq(&mut g, Address::COLUMN_DISTRICT, &self.district_eq);
+        neq(&mut g, Address::COLUMN_DISTRICT, &self.district_not_eq);
+        is_in(&mut g, Address::COLUMN_DISTRICT, &self.district_in);
+        is_not_in(&mut g, Address::COLUMN_DISTRICT, &self.district_not_in);
+
+        eq(&mut g, Address::COLUMN_POSTAL_CODE, &self.postal_code_eq);
+        is_in(&mut g, Address::COLUMN_POSTAL_CODE, &self.postal_code_in);
+
+        eq(&mut g, Address::COLUMN_PHONE, &self.phone_eq);
+
+        join_column(
+            &mut g,
+            &self.city,
+            Address::COLUMN_CITY_ID,
+            City::COLUMN_CITY_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
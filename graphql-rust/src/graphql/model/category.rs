@@ -1,110 +1,70 @@
-use crate::graphql::core::filter::{
-    and_filters, eq, is_in, is_not_in, neq, or_filters, InputFilter, TypeInfo,
-};
-use crate::graphql::core::loader::{FromRow, WithId};
-use crate::query::{Combinator, FilterGroup};
-use crate::util::MaybeOwned;
+use crate::graphql::core::order::{NullsOrder, OrderDirection, OrderInput, SortKey};
+use crate::graphql::core::prefetch::Prefetch;
+use crate::query::ProjectedRelation;
 use anyhow::Error;
-use async_graphql::{InputObject, SimpleObject};
-use rustc_hash::FxHashMap;
-use std::sync::LazyLock;
-use tokio_postgres::Row;
+use async_graphql::{Enum, InputObject, SelectionField, SimpleObject};
+use sakila_derive::{SakilaEntity, SakilaFilter};
+use tokio_postgres::types::ToSql;
 
-#[derive(Debug, Clone, Default, SimpleObject)]
+#[derive(Debug, Clone, Default, SimpleObject, SakilaEntity)]
 pub struct Category {
-    #[graphql(skip)]
+    #[sakila(primary_key)]
     pub category_id: i32,
 
     pub name: String,
 }
 
-impl Category {
-    pub const COLUMN_NAME: &'static str = "name";
-    pub const COLUMN_CATEGORY_ID: &'static str = "category_id";
-}
-
-impl TypeInfo for Category {
-    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
-        LazyLock::new(|| {
-            let mut map = FxHashMap::default();
-            map.insert("name", Self::COLUMN_NAME);
-            map
-        });
-}
-
-impl WithId<i32> for Category {
-    fn id(&self) -> &i32 {
-        &self.category_id
-    }
-}
-
-impl FromRow for Category {
-    fn from_row(row: &Row) -> Result<Self, Error>
-    where
-        Self: Sized,
-    {
-        let mut result = Self::default();
-        for col in row.columns() {
-            match col.name() {
-                Self::COLUMN_NAME => result.name = row.try_get(Self::COLUMN_NAME)?,
-                Self::COLUMN_CATEGORY_ID => {
-                    result.category_id = row.try_get(Self::COLUMN_CATEGORY_ID)?
-                }
-                col => tracing::debug!(
-                    "fetched unknown column '{}' for type '{}'",
-                    col,
-                    std::any::type_name::<Self>()
-                ),
-            }
-        }
-
-        Ok(result)
+impl Prefetch for Category {
+    fn relation<'s>(
+        _field: SelectionField<'s>,
+        _limit: &'s (dyn ToSql + Sync),
+    ) -> Result<Option<ProjectedRelation<'s>>, Error> {
+        Ok(None)
     }
 }
 
-#[derive(Debug, Clone, Default, InputObject)]
+#[derive(Debug, Clone, Default, InputObject, SakilaFilter)]
+#[sakila(table = "category", entity = Category)]
 pub struct CategoryFilter {
     pub name_eq: Option<String>,
     pub name_in: Option<Vec<String>>,
 
     pub name_not_eq: Option<String>,
     pub name_not_in: Option<Vec<String>>,
+
+    pub name_contains: Option<String>,
+    pub name_i_contains: Option<String>,
+    pub name_starts_with: Option<String>,
+    pub name_ends_with: Option<String>,
+    pub name_ilike: Option<String>,
+    pub name_regex: Option<String>,
 }
 
-impl InputFilter for CategoryFilter {
-    const TABLE_NAME: &'static str = "category";
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum CategoryOrderField {
+    Name,
+}
 
-    fn or_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        None
-    }
+#[derive(Debug, Clone, InputObject)]
+pub struct CategoryOrder {
+    pub field: CategoryOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
 
-    fn and_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        None
+impl OrderInput for CategoryOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            CategoryOrderField::Name => SortKey::Field("name"),
+        }
     }
 
-    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
-        let mut g = match collector.combinator() == Combinator::And {
-            true => MaybeOwned::Borrowed(collector),
-            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
-        };
-
-        and_filters(&mut g, self);
-        or_filters(&mut g, self);
-
-        eq(&mut g, Category::COLUMN_NAME, &self.name_eq);
-        neq(&mut g, Category::COLUMN_NAME, &self.name_not_eq);
-
-        is_in(&mut g, Category::COLUMN_NAME, &self.name_in);
-        is_not_in(&mut g, Category::COLUMN_NAME, &self.name_not_in);
+    fn direction(&self) -> OrderDirection {
+        self.direction
+    }
 
-        if let MaybeOwned::Owned(g) = g {
-            collector.add_group(g);
-        }
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
     }
 }
@@ -0,0 +1,160 @@
+use crate::graphql::core::filter::{
+    and_filters, contains, eq, is_in, is_not_in, join_column, neq, not_filter, or_filters,
+    InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::loader::CountryLoader;
+use crate::graphql::model::{Country, CountryFilter};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct City {
+    #[graphql(skip)]
+    pub city_id: i32,
+
+    #[graphql(skip)]
+    pub country_id: i32,
+
+    pub city: String,
+}
+
+#[ComplexObject]
+impl City {
+    pub const COLUMN_CITY: &'static str = "city";
+    pub const COLUMN_CITY_ID: &'static str = "city_id";
+    pub const COLUMN_COUNTRY_ID: &'static str = "country_id";
+
+    async fn country<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Country> {
+        let country = ctx
+            .data_unchecked::<DataLoader<CountryLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Country>(ctx, self.country_id)?)
+            .await?;
+
+        referenced(country, "country", self.country_id)
+    }
+}
+
+impl TypeInfo for City {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_CITY_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("city", Self::COLUMN_CITY);
+            map.insert("country", Self::COLUMN_COUNTRY_ID);
+            map
+        });
+}
+
+impl WithId<i32> for City {
+    fn id(&self) -> &i32 {
+        &self.city_id
+    }
+}
+
+impl FromRow for City {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_CITY,
+        Self::COLUMN_CITY_ID,
+        Self::COLUMN_COUNTRY_ID,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_CITY => result.city = row.try_get(Self::COLUMN_CITY)?,
+                Self::COLUMN_CITY_ID => result.city_id = row.try_get(Self::COLUMN_CITY_ID)?,
+                Self::COLUMN_COUNTRY_ID => {
+                    result.country_id = row.try_get(Self::COLUMN_COUNTRY_ID)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct CityFilter {
+    pub country: Option<Box<CountryFilter>>,
+
+    pub city_eq: Option<String>,
+    pub city_in: Option<Vec<String>>,
+
+    pub city_not_eq: Option<String>,
+    pub city_not_in: Option<Vec<String>>,
+
+    pub city_contains: Option<String>,
+}
+
+impl InputFilter for CityFilter {
+    const TABLE_NAME: &'static str = "city";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, City::COLUMN_CITY, &self.city_eq);
+        neq(&mut g, City::COLUMN_CITY, &self.city_not_eq);
+
+        is_in(&mut g, City::COLUMN_CITY, &self.city_in);
+        is_not_in(&mut g, City::COLUMN_CITY, &self.city_not_in);
+
+        contains(&mut g, City::COLUMN_CITY, &self.city_contains);
+
+        join_column(
+            &mut g,
+            &self.country,
+            City::COLUMN_COUNTRY_ID,
+            Country::COLUMN_COUNTRY_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::graphql::core::filter::{
+    and_filters, contains, eq, is_in, is_not_in, neq, not_filter, or_filters, InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{FromRow, WithId};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::{InputObject, SimpleObject};
+use rustc_hash::FxHashMap;
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+pub struct Country {
+    #[graphql(skip)]
+    pub country_id: i32,
+
+    pub country: String,
+}
+
+impl Country {
+    pub const COLUMN_COUNTRY: &'static str = "country";
+    pub const COLUMN_COUNTRY_ID: &'static str = "country_id";
+}
+
+impl TypeInfo for Country {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_COUNTRY_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("country", Self::COLUMN_COUNTRY);
+            map
+        });
+}
+
+impl WithId<i32> for Country {
+    fn id(&self) -> &i32 {
+        &self.country_id
+    }
+}
+
+impl FromRow for Country {
+    const COLUMNS: &'static [&'static str] = &[Self::COLUMN_COUNTRY, Self::COLUMN_COUNTRY_ID];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_COUNTRY => result.country = row.try_get(Self::COLUMN_COUNTRY)?,
+                Self::COLUMN_COUNTRY_ID => {
+                    result.country_id = row.try_get(Self::COLUMN_COUNTRY_ID)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct CountryFilter {
+    pub country_eq: Option<String>,
+    pub country_in: Option<Vec<String>>,
+
+    pub country_not_eq: Option<String>,
+    pub country_not_in: Option<Vec<String>>,
+
+    pub country_contains: Option<String>,
+}
+
+impl InputFilter for CountryFilter {
+    const TABLE_NAME: &'static str = "country";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, Country::COLUMN_COUNTRY, &self.country_eq);
+        neq(&mut g, Country::COLUMN_COUNTRY, &self.country_not_eq);
+
+        is_in(&mut g, Country::COLUMN_COUNTRY, &self.country_in);
+        is_not_in(&mut g, Country::COLUMN_COUNTRY, &self.country_not_in);
+
+        contains(&mut g, Country::COLUMN_COUNTRY, &self.country_contains);
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{
+    and_filters, contains, eq, gte, is_in, is_not_in, join_column, lte, neq, not_filter,
+    or_filters, InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::core::order::{sort_order, NullsOrder, OrderDirection, OrderInput, SortKey};
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::query::query_by_column;
+use crate::graphql::loader::{AddressLoader, CustomerRentalLoader};
+use crate::graphql::model::{Address, AddressFilter, Rental, RentalFilter, RentalOrder};
+use crate::query::{Combinator, FilterGroup, Operation, ValueFilter};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use time::Date;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Customer {
+    #[graphql(skip)]
+    pub customer_id: i32,
+
+    #[graphql(skip)]
+    pub store_id: i32,
+
+    #[graphql(skip)]
+    pub address_id: i32,
+
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub active: bool,
+    pub create_date: Date,
+}
+
+impl Default for Customer {
+    fn default() -> Self {
+        Self {
+            customer_id: 0,
+            store_id: 0,
+            address_id: 0,
+            first_name: String::new(),
+            last_name: String::new(),
+            email: None,
+            active: false,
+            create_date: Date::MIN,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Customer {
+    pub const COLUMN_FIRST_NAME: &'static str = "first_name";
+    pub const COLUMN_LAST_NAME: &'static str = "last_name";
+    pub const COLUMN_EMAIL: &'static str = "email";
+    pub const COLUMN_ACTIVE: &'static str = "activebool";
+    pub const COLUMN_CREATE_DATE: &'static str = "create_date";
+    pub const COLUMN_CUSTOMER_ID: &'static str = "customer_id";
+    pub const COLUMN_STORE_ID: &'static str = "store_id";
+    pub const COLUMN_ADDRESS_ID: &'static str = "address_id";
+
+    async fn address<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Address> {
+        let address = ctx
+            .data_unchecked::<DataLoader<AddressLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Address>(ctx, self.address_id)?)
+            .await?;
+
+        referenced(address, "address", self.address_id)
+    }
+
+    async fn rentals<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<RentalFilter>,
+        order_by: Option<Vec<RentalOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<Rental>> {
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Rental, _>(&order_by)?;
+
+        if filter.is_none() && order.is_empty() && page.is_empty() {
+            let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+            let rentals = ctx
+                .data_unchecked::<DataLoader<CustomerRentalLoader, HashMapCache<FxBuildHasher>>>()
+                .load_one(self.customer_id)
+                .await?;
+
+            return Ok(rentals
+                .unwrap_or_default()
+                .into_iter()
+                .take(max_page_size as usize)
+                .collect());
+        }
+
+        let customer =
+            ValueFilter::new(Operation::Eq, Rental::COLUMN_CUSTOMER_ID, &self.customer_id);
+
+        Ok(query_by_column(ctx, &filter, customer, order, page).await?)
+    }
+}
+
+impl TypeInfo for Customer {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_CUSTOMER_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("firstName", Self::COLUMN_FIRST_NAME);
+            map.insert("lastName", Self::COLUMN_LAST_NAME);
+            map.insert("email", Self::COLUMN_EMAIL);
+            map.insert("active", Self::COLUMN_ACTIVE);
+            map.insert("createDate", Self::COLUMN_CREATE_DATE);
+            map.insert("address", Self::COLUMN_ADDRESS_ID);
+            map.insert("rentals", Self::COLUMN_CUSTOMER_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Customer {
+    fn id(&self) -> &i32 {
+        &self.customer_id
+    }
+}
+
+impl FromRow for Customer {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_FIRST_NAME,
+        Self::COLUMN_LAST_NAME,
+        Self::COLUMN_EMAIL,
+        Self::COLUMN_ACTIVE,
+        Self::COLUMN_CREATE_DATE,
+        Self::COLUMN_CUSTOMER_ID,
+        Self::COLUMN_STORE_ID,
+        Self::COLUMN_ADDRESS_ID,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_FIRST_NAME => {
+                    result.first_name = row.try_get(Self::COLUMN_FIRST_NAME)?
+                }
+                Self::COLUMN_LAST_NAME => result.last_name = row.try_get(Self::COLUMN_LAST_NAME)?,
+                Self::COLUMN_EMAIL => result.email = row.try_get(Self::COLUMN_EMAIL)?,
+                Self::COLUMN_ACTIVE => result.active = row.try_get(Self::COLUMN_ACTIVE)?,
+                Self::COLUMN_CREATE_DATE => {
+                    result.create_date = row.try_get(Self::COLUMN_CREATE_DATE)?
+                }
+                Self::COLUMN_CUSTOMER_ID => {
+                    result.customer_id = row.try_get(Self::COLUMN_CUSTOMER_ID)?
+                }
+                Self::COLUMN_STORE_ID => result.store_id = row.try_get(Self::COLUMN_STORE_ID)?,
+                Self::COLUMN_ADDRESS_ID => {
+                    result.address_id = row.try_get(Self::COLUMN_ADDRESS_ID)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct CustomerFilter {
+    pub and: Option<Vec<CustomerFilter>>,
+    pub or: Option<Vec<CustomerFilter>>,
+    pub not: Option<Box<CustomerFilter>>,
+
+    pub address: Option<Box<AddressFilter>>,
+
+    pub first_name_eq: Option<String>,
+    pub first_name_in: Option<Vec<String>>,
+
+    pub first_name_not_eq: Option<String>,
+    pub first_name_not_in: Option<Vec<String>>,
+
+    pub last_name_eq: Option<String>,
+    pub last_name_in: Option<Vec<String>>,
+
+    pub last_name_not_eq: Option<String>,
+    pub last_name_not_in: Option<Vec<String>>,
+
+    pub email_eq: Option<String>,
+    pub email_contains: Option<String>,
+
+    pub active_eq: Option<bool>,
+
+    pub create_date_gte: Option<Date>,
+    pub create_date_lte: Option<Date>,
+}
+
+impl InputFilter for CustomerFilter {
+    const TABLE_NAME: &'static str = "customer";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.or.as_deref()
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.and.as_deref()
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        self.not.as_deref()
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, Customer::COLUMN_FIRST_NAME, &self.first_name_eq);
+        neq(&mut g, Customer::COLUMN_FIRST_NAME, &self.first_name_not_eq);
+
+        is_in(&mut g, Customer::COLUMN_FIRST_NAME, &self.first_name_in);
+        is_not_in(&mut g, Customer::COLUMN_FIRST_NAME, &self.first_name_not_in);
+
+        eq(&mut g, Customer::COLUMN_LAST_NAME, &self.last_name_eq);
+        neq(&mut g, Customer::COLUMN_LAST_NAME, &self.last_name_not_eq);
+
+        is_in(&mut g, Customer::COLUMN_LAST_NAME, &self.last_name_in);
+        is_not_in(&mut g, Customer::COLUMN_LAST_NAME, &self.last_name_not_in);
+
+        eq(&mut g, Customer::COLUMN_EMAIL, &self.email_eq);
+        contains(&mut g, Customer::COLUMN_EMAIL, &self.email_contains);
+
+        eq(&mut g, Customer::COLUMN_ACTIVE, &self.active_eq);
+
+        gte(&mut g, Customer::COLUMN_CREATE_DATE, &self.create_date_gte);
+        lte(&mut g, Customer::COLUMN_CREATE_DATE, &self.create_date_lte);
+
+        join_column(
+            &mut g,
+            &self.address,
+            Customer::COLUMN_ADDRESS_ID,
+            Address::COLUMN_ADDRESS_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum CustomerOrderField {
+    FirstName,
+    LastName,
+    Email,
+    CreateDate,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CustomerOrder {
+    pub field: CustomerOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl OrderInput for CustomerOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            CustomerOrderField::FirstName => SortKey::Field("firstName"),
+            CustomerOrderField::LastName => SortKey::Field("lastName"),
+            CustomerOrderField::Email => SortKey::Field("email"),
+            CustomerOrderField::CreateDate => SortKey::Field("createDate"),
+        }
+    }
+
+    fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
+    }
+}
@@ -1,62 +1,144 @@
-use crate::graphql::core::filter::{
-    and_filters, contains, eq, gt, gte, is_in, is_not_in, join_column, join_table, lt, lte, neq,
-    or_filters, InputFilter, TypeInfo,
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FilteredKey};
+use crate::graphql::core::order::{
+    join_column_key, join_table_count_key, sort_order, NullsOrder, OrderDirection, OrderInput,
+    SortKey,
 };
-use crate::graphql::core::loader::{FromRow, WithId};
-use crate::graphql::core::query::query;
+use crate::graphql::core::page::PageArgs;
+use crate::graphql::core::prefetch::{
+    json_decimal, json_timestamp, projection, relation_key, Prefetch, Prefetched,
+};
+use crate::graphql::core::query::{query, query_count};
 use crate::graphql::loader::{
-    ActorLoader, CategoryLoader, FilmActorIdLoader, FilmCategoryIdLoader, LanguageLoader,
+    ActorLoader, CategoryLoader, FilmActorCountLoader, FilmActorFilteredLoader, FilmActorIdLoader,
+    FilmCategoryIdLoader, FilmInventoryLoader, LanguageLoader,
 };
 use crate::graphql::model::join_tables::{
     JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID, JOIN_TABLE__FILM_ACTOR__FILM_ID,
     JOIN_TABLE__FILM_CATEGORY, JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
-    JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+    JOIN_TABLE__FILM_CATEGORY__FILM_ID, JOIN_TABLE__INVENTORY, JOIN_TABLE__INVENTORY__FILM_ID,
+    JOIN_TABLE__INVENTORY__STORE_ID,
 };
 use crate::graphql::model::{
-    Actor, ActorFilter, Category, CategoryFilter, Language, LanguageFilter,
+    Actor, ActorFilter, ActorOrder, Category, CategoryFilter, CategoryOrder, Inventory, Language,
+    LanguageFilter, Store, StoreFilter,
 };
-use crate::query::{Combinator, FilterGroup, JoinedTable};
-use crate::util::MaybeOwned;
+use crate::query::{JoinedTable, ProjectedRelation, RelationJoin, RANK_COLUMN};
 use anyhow::Error;
 use async_graphql::dataloader::{DataLoader, HashMapCache};
-use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
-use rustc_hash::{FxBuildHasher, FxHashMap};
-use std::sync::LazyLock;
-use tokio_postgres::Row;
-
-#[derive(Debug, Clone, Default, SimpleObject)]
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SelectionField, SimpleObject};
+use postgres_types::{FromSql, ToSql};
+use rust_decimal::Decimal;
+use rustc_hash::FxBuildHasher;
+use sakila_derive::{SakilaEntity, SakilaFilter};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, SimpleObject, SakilaEntity)]
 #[graphql(complex)]
 pub struct Film {
-    #[graphql(skip)]
+    #[sakila(
+        primary_key,
+        resolves("actors", "actorsCount", "categories", "inventory")
+    )]
     pub film_id: i32,
 
     #[graphql(skip)]
+    #[sakila(resolves("language"))]
     pub language_id: i32,
 
     #[graphql(skip)]
+    #[sakila(resolves("originalLanguage"))]
     pub original_language_id: Option<i32>,
 
     pub title: String,
     pub description: String,
     pub length: i16,
+    pub release_year: Option<i32>,
+    pub rental_duration: i16,
+    #[sakila(json = json_decimal)]
+    pub rental_rate: Decimal,
+    #[sakila(json = json_decimal)]
+    pub replacement_cost: Decimal,
+    pub rating: Option<MpaaRating>,
+    pub special_features: Option<Vec<String>>,
+    #[sakila(json = json_timestamp)]
+    pub last_update: OffsetDateTime,
+
+    // only available when the films are filtered by a text search
+    #[sakila(computed, column = RANK_COLUMN)]
+    pub rank: Option<f32>,
+
+    #[graphql(skip)]
+    #[sakila(prefetched)]
+    pub prefetched: Prefetched,
+}
+
+impl Default for Film {
+    fn default() -> Self {
+        Self {
+            film_id: 0,
+            language_id: 0,
+            original_language_id: None,
+            title: String::new(),
+            description: String::new(),
+            length: 0,
+            release_year: None,
+            rental_duration: 0,
+            rental_rate: Decimal::ZERO,
+            replacement_cost: Decimal::ZERO,
+            rating: None,
+            special_features: None,
+            last_update: OffsetDateTime::UNIX_EPOCH,
+            rank: None,
+            prefetched: Prefetched::default(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum, ToSql, FromSql, Deserialize)]
+#[postgres(name = "mpaa_rating")]
+pub enum MpaaRating {
+    #[postgres(name = "G")]
+    #[serde(rename = "G")]
+    G,
+    #[postgres(name = "PG")]
+    #[serde(rename = "PG")]
+    Pg,
+    #[postgres(name = "PG-13")]
+    #[serde(rename = "PG-13")]
+    #[graphql(name = "PG_13")]
+    Pg13,
+    #[postgres(name = "R")]
+    #[serde(rename = "R")]
+    R,
+    #[postgres(name = "NC-17")]
+    #[serde(rename = "NC-17")]
+    #[graphql(name = "NC_17")]
+    Nc17,
 }
 
 #[ComplexObject]
 impl Film {
-    pub const COLUMN_TITLE: &'static str = "title";
-    pub const COLUMN_LENGTH: &'static str = "length";
-    pub const COLUMN_LANG_ID: &'static str = "language_id";
-    pub const COLUMN_DESCRIPTION: &'static str = "description";
-    pub const COLUMN_FILM_ID: &'static str = "film_id";
-    pub const COLUMN_LANGUAGE_ID: &'static str = "language_id";
-    pub const COLUMN_ORIG_LANG_ID: &'static str = "original_language_id";
+    pub const COLUMN_FULLTEXT: &'static str = "fulltext";
 
     async fn actors<'a>(
         &self,
         ctx: &Context<'a>,
         filter: Option<ActorFilter>,
+        order_by: Option<Vec<ActorOrder>>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+        #[graphql(validator(minimum = 0))] offset: Option<i32>,
     ) -> async_graphql::Result<Vec<Actor>> {
-        if filter.is_none() {
+        if let Some(actors) = self.prefetched.many::<Actor>(ctx)? {
+            return Ok(actors);
+        }
+
+        let page = PageArgs::new(first, offset);
+        let order = sort_order::<Actor, _>(&order_by)?;
+
+        if filter.is_none() && order.is_empty() && page.is_empty() {
             let ids = ctx
                 .data_unchecked::<DataLoader<FilmActorIdLoader, HashMapCache<FxBuildHasher>>>()
                 .load_one(self.film_id)
@@ -66,12 +148,47 @@ impl Film {
                 return Ok(vec![]);
             };
 
-            let actors = ctx
+            let keys = ColumnsKey::many::<Actor>(ctx, &ids)?;
+            let mut actors = ctx
                 .data_unchecked::<DataLoader<ActorLoader, HashMapCache<FxBuildHasher>>>()
-                .load_many(ids)
+                .load_many(keys.iter().cloned())
+                .await?;
+
+            let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+            return Ok(keys
+                .iter()
+                .filter_map(|key| actors.remove(key))
+                .take(max_page_size as usize)
+                .collect());
+        }
+
+        let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+        let actors = ctx
+            .data_unchecked::<DataLoader<FilmActorFilteredLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(FilteredKey::new(
+                self.film_id,
+                filter,
+                order_by,
+                page,
+                max_page_size,
+            ))
+            .await?;
+
+        Ok(actors.unwrap_or_default())
+    }
+
+    async fn actors_count<'a>(
+        &self,
+        ctx: &Context<'a>,
+        filter: Option<ActorFilter>,
+    ) -> async_graphql::Result<i64> {
+        if filter.is_none() {
+            let count = ctx
+                .data_unchecked::<DataLoader<FilmActorCountLoader, HashMapCache<FxBuildHasher>>>()
+                .load_one(self.film_id)
                 .await?;
 
-            return Ok(actors.into_values().collect());
+            return Ok(count.unwrap_or_default());
         }
 
         let joined_table = JoinedTable {
@@ -82,33 +199,87 @@ impl Film {
             join_table_filter_col_val: &self.film_id,
         };
 
-        Ok(query(ctx, &filter, Some(joined_table)).await?)
+        Ok(query_count(ctx, &filter, Some(joined_table)).await?)
     }
 
-    async fn categories<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Vec<Category>> {
-        let ids = ctx
-            .data_unchecked::<DataLoader<FilmCategoryIdLoader, HashMapCache<FxBuildHasher>>>()
+    async fn categories<'a>(
+        &self,
+        ctx: &Context<'a>,
+        order_by: Option<Vec<CategoryOrder>>,
+    ) -> async_graphql::Result<Vec<Category>> {
+        if let Some(categories) = self.prefetched.many::<Category>(ctx)? {
+            return Ok(categories);
+        }
+
+        let order = sort_order::<Category, _>(&order_by)?;
+
+        if order.is_empty() {
+            let ids = ctx
+                .data_unchecked::<DataLoader<FilmCategoryIdLoader, HashMapCache<FxBuildHasher>>>()
+                .load_one(self.film_id)
+                .await?;
+
+            let Some(ids) = ids else {
+                return Ok(vec![]);
+            };
+
+            let loader =
+                ctx.data_unchecked::<DataLoader<CategoryLoader, HashMapCache<FxBuildHasher>>>();
+            let keys = ColumnsKey::many::<Category>(ctx, &ids)?;
+            let mut categories = loader.load_many(keys.iter().cloned()).await?;
+
+            return Ok(keys
+                .iter()
+                .filter_map(|key| categories.remove(key))
+                .collect());
+        }
+
+        let joined_table = JoinedTable {
+            join_table: JOIN_TABLE__FILM_CATEGORY,
+            join_table_join_col: JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+            data_table_join_col: Category::COLUMN_CATEGORY_ID,
+            join_table_filter_col: Self::COLUMN_FILM_ID,
+            join_table_filter_col_val: &self.film_id,
+        };
+
+        let filter: Option<CategoryFilter> = None;
+        Ok(query(ctx, &filter, Some(joined_table), order, PageArgs::default()).await?)
+    }
+
+    async fn inventory<'a>(
+        &self,
+        ctx: &Context<'a>,
+        store_id: Option<i32>,
+    ) -> async_graphql::Result<Vec<Inventory>> {
+        let inventory = ctx
+            .data_unchecked::<DataLoader<FilmInventoryLoader, HashMapCache<FxBuildHasher>>>()
             .load_one(self.film_id)
             .await?;
 
-        let Some(ids) = ids else {
+        let Some(inventory) = inventory else {
             return Ok(vec![]);
         };
 
-        let loader =
-            ctx.data_unchecked::<DataLoader<CategoryLoader, HashMapCache<FxBuildHasher>>>();
-        let categories = loader.load_many(ids).await?;
-
-        Ok(categories.into_values().collect())
+        Ok(inventory
+            .into_iter()
+            .filter(|item| match store_id {
+                Some(id) => item.store_id == id,
+                None => true,
+            })
+            .collect())
     }
 
     async fn language<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Language> {
+        if let Some(language) = self.prefetched.one::<Language>(ctx)? {
+            return referenced(language, "language", self.language_id);
+        }
+
         let language = ctx
             .data_unchecked::<DataLoader<LanguageLoader, HashMapCache<FxBuildHasher>>>()
-            .load_one(self.language_id)
+            .load_one(ColumnsKey::new::<Language>(ctx, self.language_id)?)
             .await?;
 
-        Ok(language.unwrap_or_else(|| Language::default()))
+        referenced(language, "language", self.language_id)
     }
 
     async fn original_language<'a>(
@@ -121,160 +292,283 @@ impl Film {
 
         let language = ctx
             .data_unchecked::<DataLoader<LanguageLoader, HashMapCache<FxBuildHasher>>>()
-            .load_one(lang_id)
+            .load_one(ColumnsKey::new::<Language>(ctx, lang_id)?)
             .await?;
 
         Ok(language)
     }
 }
 
-impl TypeInfo for Film {
-    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
-        LazyLock::new(|| {
-            let mut map = FxHashMap::default();
-            map.insert("title", Self::COLUMN_TITLE);
-            map.insert("description", Self::COLUMN_DESCRIPTION);
-            map.insert("length", Self::COLUMN_LENGTH);
-            map.insert("actors", Self::COLUMN_FILM_ID);
-            map.insert("categories", Self::COLUMN_FILM_ID);
-            map.insert("language", Self::COLUMN_LANGUAGE_ID);
-            map.insert("originalLanguage", Self::COLUMN_ORIG_LANG_ID);
-            map
-        });
-}
-
-impl WithId<i32> for Film {
-    fn id(&self) -> &i32 {
-        &self.film_id
-    }
-}
-
-impl FromRow for Film {
-    fn from_row(row: &Row) -> Result<Self, Error>
-    where
-        Self: Sized,
-    {
-        let mut result = Self::default();
-        for col in row.columns() {
-            match col.name() {
-                Self::COLUMN_TITLE => result.title = row.try_get(Self::COLUMN_TITLE)?,
-                Self::COLUMN_DESCRIPTION => {
-                    result.description = row.try_get(Self::COLUMN_DESCRIPTION)?
-                }
-                Self::COLUMN_LENGTH => result.length = row.try_get(Self::COLUMN_LENGTH)?,
-                Self::COLUMN_FILM_ID => result.film_id = row.try_get(Self::COLUMN_FILM_ID)?,
-                Self::COLUMN_LANG_ID => result.language_id = row.try_get(Self::COLUMN_LANG_ID)?,
-                Self::COLUMN_ORIG_LANG_ID => {
-                    result.original_language_id = row.try_get(Self::COLUMN_ORIG_LANG_ID)?
-                }
-                col => tracing::debug!(
-                    "fetched unknown column '{}' for type '{}'",
-                    col,
-                    std::any::type_name::<Self>()
-                ),
-            }
-        }
+impl Prefetch for Film {
+    fn relation<'s>(
+        field: SelectionField<'s>,
+        limit: &'s (dyn ToSql + Sync),
+    ) -> Result<Option<ProjectedRelation<'s>>, Error> {
+        let relation = match field.name() {
+            "actors" => ProjectedRelation {
+                key: relation_key(&field),
+                table: ActorFilter::TABLE_NAME,
+                join: RelationJoin::ToMany {
+                    parent_column: Self::COLUMN_FILM_ID,
+                    join_table: JOIN_TABLE__FILM_ACTOR,
+                    join_table_parent_col: JOIN_TABLE__FILM_ACTOR__FILM_ID,
+                    join_table_child_col: JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+                    child_column: Actor::COLUMN_ACTOR_ID,
+                    primary_key: Actor::PRIMARY_KEY,
+                    limit,
+                },
+                projection: projection::<Actor>(field, limit)?,
+            },
+            "categories" => ProjectedRelation {
+                key: relation_key(&field),
+                table: CategoryFilter::TABLE_NAME,
+                join: RelationJoin::ToMany {
+                    parent_column: Self::COLUMN_FILM_ID,
+                    join_table: JOIN_TABLE__FILM_CATEGORY,
+                    join_table_parent_col: JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+                    join_table_child_col: JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+                    child_column: Category::COLUMN_CATEGORY_ID,
+                    primary_key: Category::PRIMARY_KEY,
+                    limit,
+                },
+                projection: projection::<Category>(field, limit)?,
+            },
+            "language" => ProjectedRelation {
+                key: relation_key(&field),
+                table: LanguageFilter::TABLE_NAME,
+                join: RelationJoin::ToOne {
+                    parent_column: Self::COLUMN_LANGUAGE_ID,
+                    child_column: Language::COLUMN_LANGUAGE_ID,
+                },
+                projection: projection::<Language>(field, limit)?,
+            },
+            _ => return Ok(None),
+        };
 
-        Ok(result)
+        Ok(Some(relation))
     }
 }
 
-#[derive(Debug, Clone, Default, InputObject)]
+#[derive(Debug, Clone, Default, InputObject, SakilaFilter)]
+#[sakila(
+    table = "film",
+    entity = Film,
+    join_table(
+        name = "actors",
+        table = JOIN_TABLE__FILM_ACTOR,
+        table_parent_col = JOIN_TABLE__FILM_ACTOR__FILM_ID,
+        table_child_col = JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+        parent_col = Film::COLUMN_FILM_ID,
+        child_col = Actor::COLUMN_ACTOR_ID,
+    ),
+    join_table(
+        name = "categories",
+        table = JOIN_TABLE__FILM_CATEGORY,
+        table_parent_col = JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+        table_child_col = JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+        parent_col = Film::COLUMN_FILM_ID,
+        child_col = Category::COLUMN_CATEGORY_ID,
+    ),
+    join_table(
+        name = "stores",
+        table = JOIN_TABLE__INVENTORY,
+        table_parent_col = JOIN_TABLE__INVENTORY__FILM_ID,
+        table_child_col = JOIN_TABLE__INVENTORY__STORE_ID,
+        parent_col = Film::COLUMN_FILM_ID,
+        child_col = Store::COLUMN_STORE_ID,
+    )
+)]
 pub struct FilmFilter {
+    #[sakila(and)]
     pub and: Option<Vec<FilmFilter>>,
+    #[sakila(or)]
     pub or: Option<Vec<FilmFilter>>,
+    #[sakila(not)]
+    pub not: Option<Box<FilmFilter>>,
 
+    #[sakila(join_table = "actors")]
     pub actor: Option<Box<ActorFilter>>,
+    #[sakila(join_table = "actors")]
+    pub actors_some: Option<Box<ActorFilter>>,
+    #[sakila(join_table = "actors", every)]
+    pub actors_every: Option<Box<ActorFilter>>,
+    #[sakila(join_table = "actors", none)]
+    pub actors_none: Option<Box<ActorFilter>>,
+    #[sakila(join_table = "categories")]
     pub category: Option<Box<CategoryFilter>>,
+    #[sakila(join_table = "categories")]
+    pub categories_some: Option<Box<CategoryFilter>>,
+    #[sakila(join_table = "categories", every)]
+    pub categories_every: Option<Box<CategoryFilter>>,
+    #[sakila(join_table = "categories", none)]
+    pub categories_none: Option<Box<CategoryFilter>>,
+    #[sakila(join_column(
+        parent_col = Film::COLUMN_LANGUAGE_ID,
+        child_col = Language::COLUMN_LANGUAGE_ID
+    ))]
     pub language: Option<Box<LanguageFilter>>,
+    #[sakila(join_column(
+        parent_col = Film::COLUMN_ORIGINAL_LANGUAGE_ID,
+        child_col = Language::COLUMN_LANGUAGE_ID
+    ))]
     pub original_language: Option<Box<LanguageFilter>>,
+    #[sakila(join_table = "stores")]
+    pub in_store: Option<Box<StoreFilter>>,
+
+    #[sakila(count_filter = "actors")]
+    pub actor_count_filter: Option<Box<ActorFilter>>,
+    #[sakila(count = "actors")]
+    pub actor_count_eq: Option<i64>,
+    #[sakila(count = "actors")]
+    pub actor_count_gt: Option<i64>,
+    #[sakila(count = "actors")]
+    pub actor_count_gte: Option<i64>,
+    #[sakila(count = "actors")]
+    pub actor_count_lt: Option<i64>,
+    #[sakila(count = "actors")]
+    pub actor_count_lte: Option<i64>,
+
+    #[sakila(count_filter = "categories")]
+    pub category_count_filter: Option<Box<CategoryFilter>>,
+    #[sakila(count = "categories")]
+    pub category_count_eq: Option<i64>,
+    #[sakila(count = "categories")]
+    pub category_count_gt: Option<i64>,
+    #[sakila(count = "categories")]
+    pub category_count_gte: Option<i64>,
+    #[sakila(count = "categories")]
+    pub category_count_lt: Option<i64>,
+    #[sakila(count = "categories")]
+    pub category_count_lte: Option<i64>,
+
+    #[sakila(text_search = Film::COLUMN_FULLTEXT)]
+    pub search: Option<String>,
 
     pub title_eq: Option<String>,
     pub title_not_eq: Option<String>,
     pub title_in: Option<Vec<String>>,
     pub title_not_in: Option<Vec<String>>,
     pub title_contains: Option<String>,
+    pub title_i_contains: Option<String>,
+    pub title_starts_with: Option<String>,
+    pub title_ends_with: Option<String>,
+    pub title_ilike: Option<String>,
+    pub title_regex: Option<String>,
+
+    pub description_contains: Option<String>,
+    pub description_i_contains: Option<String>,
+    pub description_starts_with: Option<String>,
+    pub description_ends_with: Option<String>,
+    pub description_ilike: Option<String>,
+    pub description_regex: Option<String>,
 
     pub length_eq: Option<i16>,
     pub length_gt: Option<i16>,
     pub length_gte: Option<i16>,
     pub length_lt: Option<i16>,
     pub length_lte: Option<i16>,
+
+    pub release_year_eq: Option<i32>,
+    pub release_year_in: Option<Vec<i32>>,
+    pub release_year_gt: Option<i32>,
+    pub release_year_gte: Option<i32>,
+    pub release_year_lt: Option<i32>,
+    pub release_year_lte: Option<i32>,
+
+    pub rental_duration_eq: Option<i16>,
+    pub rental_duration_gt: Option<i16>,
+    pub rental_duration_gte: Option<i16>,
+    pub rental_duration_lt: Option<i16>,
+    pub rental_duration_lte: Option<i16>,
+
+    pub rental_rate_eq: Option<Decimal>,
+    pub rental_rate_gt: Option<Decimal>,
+    pub rental_rate_gte: Option<Decimal>,
+    pub rental_rate_lt: Option<Decimal>,
+    pub rental_rate_lte: Option<Decimal>,
+
+    pub replacement_cost_eq: Option<Decimal>,
+    pub replacement_cost_gt: Option<Decimal>,
+    pub replacement_cost_gte: Option<Decimal>,
+    pub replacement_cost_lt: Option<Decimal>,
+    pub replacement_cost_lte: Option<Decimal>,
+
+    pub rating_eq: Option<MpaaRating>,
+    pub rating_not_eq: Option<MpaaRating>,
+    pub rating_in: Option<Vec<MpaaRating>>,
+    pub rating_not_in: Option<Vec<MpaaRating>>,
+
+    #[sakila(op = "array_contains")]
+    pub special_features_contains: Option<Vec<String>>,
 }
 
-impl InputFilter for FilmFilter {
-    const TABLE_NAME: &'static str = "film";
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum FilmOrderField {
+    Title,
+    Description,
+    Length,
+    ReleaseYear,
+    RentalDuration,
+    RentalRate,
+    ReplacementCost,
+    Rating,
+    LastUpdate,
+    LanguageName,
+    OriginalLanguageName,
+    ActorCount,
+    CategoryCount,
+    Relevance,
+}
 
-    fn or_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        self.or.as_ref().map(|x| x.as_slice())
-    }
+#[derive(Debug, Clone, InputObject)]
+pub struct FilmOrder {
+    pub field: FilmOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
 
-    fn and_filters(&self) -> Option<&[Self]>
-    where
-        Self: Sized,
-    {
-        self.and.as_ref().map(|x| x.as_slice())
+impl OrderInput for FilmOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            FilmOrderField::Title => SortKey::Field("title"),
+            FilmOrderField::Description => SortKey::Field("description"),
+            FilmOrderField::Length => SortKey::Field("length"),
+            FilmOrderField::ReleaseYear => SortKey::Field("releaseYear"),
+            FilmOrderField::RentalDuration => SortKey::Field("rentalDuration"),
+            FilmOrderField::RentalRate => SortKey::Field("rentalRate"),
+            FilmOrderField::ReplacementCost => SortKey::Field("replacementCost"),
+            FilmOrderField::Rating => SortKey::Field("rating"),
+            FilmOrderField::LastUpdate => SortKey::Field("lastUpdate"),
+            FilmOrderField::LanguageName => join_column_key::<LanguageFilter>(
+                Film::COLUMN_LANGUAGE_ID,
+                Language::COLUMN_LANGUAGE_ID,
+                Language::COLUMN_NAME,
+            ),
+            FilmOrderField::OriginalLanguageName => join_column_key::<LanguageFilter>(
+                Film::COLUMN_ORIGINAL_LANGUAGE_ID,
+                Language::COLUMN_LANGUAGE_ID,
+                Language::COLUMN_NAME,
+            ),
+            FilmOrderField::ActorCount => join_table_count_key(
+                JOIN_TABLE__FILM_ACTOR,
+                JOIN_TABLE__FILM_ACTOR__FILM_ID,
+                Film::COLUMN_FILM_ID,
+            ),
+            FilmOrderField::CategoryCount => join_table_count_key(
+                JOIN_TABLE__FILM_CATEGORY,
+                JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+                Film::COLUMN_FILM_ID,
+            ),
+            FilmOrderField::Relevance => SortKey::Relevance,
+        }
     }
 
-    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
-        let mut g = match collector.combinator() == Combinator::And {
-            true => MaybeOwned::Borrowed(collector),
-            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
-        };
+    fn direction(&self) -> OrderDirection {
+        self.direction
+    }
 
-        and_filters(&mut g, self);
-        or_filters(&mut g, self);
-
-        eq(&mut g, Film::COLUMN_TITLE, &self.title_eq);
-        neq(&mut g, Film::COLUMN_TITLE, &self.title_not_eq);
-        is_in(&mut g, Film::COLUMN_TITLE, &self.title_in);
-        is_not_in(&mut g, Film::COLUMN_TITLE, &self.title_not_in);
-        contains(&mut g, Film::COLUMN_TITLE, &self.title_contains);
-
-        eq(&mut g, Film::COLUMN_LENGTH, &self.length_eq);
-        lt(&mut g, Film::COLUMN_LENGTH, &self.length_lt);
-        lte(&mut g, Film::COLUMN_LENGTH, &self.length_lte);
-        gt(&mut g, Film::COLUMN_LENGTH, &self.length_gt);
-        gte(&mut g, Film::COLUMN_LENGTH, &self.length_gte);
-
-        join_column(
-            &mut g,
-            &self.language,
-            Film::COLUMN_LANG_ID,
-            Language::LANGUAGE_ID,
-        );
-        join_column(
-            &mut g,
-            &self.original_language,
-            Film::COLUMN_ORIG_LANG_ID,
-            Language::LANGUAGE_ID,
-        );
-
-        join_table(
-            &mut g,
-            &self.category,
-            JOIN_TABLE__FILM_CATEGORY,
-            JOIN_TABLE__FILM_CATEGORY__FILM_ID,
-            JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
-            Film::COLUMN_FILM_ID,
-            Category::COLUMN_CATEGORY_ID,
-        );
-
-        join_table(
-            &mut g,
-            &self.actor,
-            JOIN_TABLE__FILM_ACTOR,
-            JOIN_TABLE__FILM_ACTOR__FILM_ID,
-            JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
-            Film::COLUMN_FILM_ID,
-            Actor::COLUMN_ACTOR_ID,
-        );
-
-        if let MaybeOwned::Owned(g) = g {
-            collector.add_group(g);
-        }
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
     }
 }
@@ -0,0 +1,202 @@
+use crate::graphql::core::loader::{ColumnsKey, FromRow};
+use crate::graphql::loader::{CategoryLoader, LanguageLoader};
+use crate::graphql::model::film::MpaaRating;
+use crate::graphql::model::join_tables::{
+    JOIN_TABLE__FILM_CATEGORY, JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+    JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+};
+use crate::graphql::model::{Category, Film, Language};
+use crate::query::{
+    Aggregate, AggregateColumn, AggregateFunction, GroupKey, AGGREGATE_COUNT_COLUMN,
+};
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use rust_decimal::Decimal;
+use rustc_hash::FxBuildHasher;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct FilmAggregate {
+    pub count: i64,
+
+    // the group keys are only set when the films are grouped by them
+    pub rating: Option<MpaaRating>,
+
+    #[graphql(skip)]
+    pub language_id: Option<i32>,
+
+    #[graphql(skip)]
+    pub category_id: Option<i32>,
+
+    pub avg: FilmNumericAggregate,
+    pub min: FilmNumericAggregate,
+    pub max: FilmNumericAggregate,
+    pub sum: FilmNumericAggregate,
+}
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+pub struct FilmNumericAggregate {
+    pub length: Option<Decimal>,
+    pub rental_rate: Option<Decimal>,
+    pub replacement_cost: Option<Decimal>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum FilmGroupBy {
+    Rating,
+    Language,
+    Category,
+}
+
+impl FilmGroupBy {
+    fn group_key(self) -> GroupKey<'static> {
+        match self {
+            FilmGroupBy::Rating => GroupKey::Column(Film::COLUMN_RATING),
+            FilmGroupBy::Language => GroupKey::Column(Film::COLUMN_LANGUAGE_ID),
+            FilmGroupBy::Category => GroupKey::JoinTable {
+                join_table: JOIN_TABLE__FILM_CATEGORY,
+                parent_table_join_col: Film::COLUMN_FILM_ID,
+                join_table_parent_col: JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+                column: JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+            },
+        }
+    }
+}
+
+#[ComplexObject]
+impl FilmAggregate {
+    async fn language<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Option<Language>> {
+        let Some(language_id) = self.language_id else {
+            return Ok(None);
+        };
+
+        let language = ctx
+            .data_unchecked::<DataLoader<LanguageLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Language>(ctx, language_id)?)
+            .await?;
+
+        Ok(language)
+    }
+
+    async fn category<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Option<Category>> {
+        let Some(category_id) = self.category_id else {
+            return Ok(None);
+        };
+
+        let category = ctx
+            .data_unchecked::<DataLoader<CategoryLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Category>(ctx, category_id)?)
+            .await?;
+
+        Ok(category)
+    }
+}
+
+impl FilmAggregate {
+    // Only the aggregates which are present in the selection set are computed
+    pub fn requested<'a>(
+        ctx: &Context<'a>,
+        group_by: &Option<Vec<FilmGroupBy>>,
+    ) -> Aggregate<'static> {
+        let mut aggregate = Aggregate::default();
+
+        for key in group_by.iter().flatten().map(|g| g.group_key()) {
+            if aggregate.group_by.iter().all(|k| k.alias() != key.alias()) {
+                aggregate.group_by.push(key);
+            }
+        }
+
+        for field in ctx.field().selection_set() {
+            let function = match field.name() {
+                "avg" => AggregateFunction::Avg,
+                "min" => AggregateFunction::Min,
+                "max" => AggregateFunction::Max,
+                "sum" => AggregateFunction::Sum,
+                _ => continue,
+            };
+
+            for column in field.selection_set() {
+                let column = match column.name() {
+                    "length" => Film::COLUMN_LENGTH,
+                    "rentalRate" => Film::COLUMN_RENTAL_RATE,
+                    "replacementCost" => Film::COLUMN_REPLACEMENT_COST,
+                    _ => continue,
+                };
+
+                let column = AggregateColumn { function, column };
+                if aggregate
+                    .columns
+                    .iter()
+                    .all(|c| c.alias() != column.alias())
+                {
+                    aggregate.columns.push(column);
+                }
+            }
+        }
+
+        aggregate
+    }
+}
+
+impl FromRow for FilmAggregate {
+    // the columns depend on the requested groups and aggregates
+    const COLUMNS: &'static [&'static str] = &[];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &name in columns {
+            match name {
+                AGGREGATE_COUNT_COLUMN => result.count = row.try_get(AGGREGATE_COUNT_COLUMN)?,
+                Film::COLUMN_RATING => result.rating = row.try_get(Film::COLUMN_RATING)?,
+                Film::COLUMN_LANGUAGE_ID => {
+                    result.language_id = row.try_get(Film::COLUMN_LANGUAGE_ID)?
+                }
+                JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID => {
+                    result.category_id = row.try_get(JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID)?
+                }
+                name => {
+                    let aggregated = name.split_once('_').and_then(|(function, column)| {
+                        let target = match function {
+                            "avg" => &mut result.avg,
+                            "min" => &mut result.min,
+                            "max" => &mut result.max,
+                            "sum" => &mut result.sum,
+                            _ => return None,
+                        };
+
+                        match column {
+                            Film::COLUMN_LENGTH => Some(&mut target.length),
+                            Film::COLUMN_RENTAL_RATE => Some(&mut target.rental_rate),
+                            Film::COLUMN_REPLACEMENT_COST => Some(&mut target.replacement_cost),
+                            _ => None,
+                        }
+                    });
+
+                    match aggregated {
+                        Some(value) => *value = row.try_get(name)?,
+                        None => anyhow::bail!(
+                            "the column '{}' is not mapped to a field of '{}'",
+                            name,
+                            std::any::type_name::<Self>()
+                        ),
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let columns = row.columns().iter().map(|c| c.name()).collect::<Vec<_>>();
+        Self::from_columns(row, &columns)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::graphql::core::filter::TypeInfo;
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::loader::{FilmLoader, StoreLoader};
+use crate::graphql::model::{Film, Store};
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct Inventory {
+    pub inventory_id: i32,
+
+    #[graphql(skip)]
+    pub film_id: i32,
+
+    #[graphql(skip)]
+    pub store_id: i32,
+}
+
+#[ComplexObject]
+impl Inventory {
+    pub const COLUMN_INVENTORY_ID: &'static str = "inventory_id";
+    pub const COLUMN_FILM_ID: &'static str = "film_id";
+    pub const COLUMN_STORE_ID: &'static str = "store_id";
+    pub const TABLE_NAME: &'static str = "inventory";
+
+    async fn film<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Film> {
+        let film = ctx
+            .data_unchecked::<DataLoader<FilmLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Film>(ctx, self.film_id)?)
+            .await?;
+
+        referenced(film, "film", self.film_id)
+    }
+
+    async fn store<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Store> {
+        let store = ctx
+            .data_unchecked::<DataLoader<StoreLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Store>(ctx, self.store_id)?)
+            .await?;
+
+        referenced(store, "store", self.store_id)
+    }
+}
+
+impl TypeInfo for Inventory {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_INVENTORY_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("inventoryId", Self::COLUMN_INVENTORY_ID);
+            map.insert("film", Self::COLUMN_FILM_ID);
+            map.insert("store", Self::COLUMN_STORE_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Inventory {
+    fn id(&self) -> &i32 {
+        &self.inventory_id
+    }
+}
+
+impl FromRow for Inventory {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_INVENTORY_ID,
+        Self::COLUMN_FILM_ID,
+        Self::COLUMN_STORE_ID,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_INVENTORY_ID => {
+                    result.inventory_id = row.try_get(Self::COLUMN_INVENTORY_ID)?
+                }
+                Self::COLUMN_FILM_ID => result.film_id = row.try_get(Self::COLUMN_FILM_ID)?,
+                Self::COLUMN_STORE_ID => result.store_id = row.try_get(Self::COLUMN_STORE_ID)?,
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
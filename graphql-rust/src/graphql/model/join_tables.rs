@@ -5,3 +5,7 @@ pub const JOIN_TABLE__FILM_ACTOR__ACTOR_ID: &'static str = "actor_id";
 pub const JOIN_TABLE__FILM_CATEGORY: &'static str = "film_category";
 pub const JOIN_TABLE__FILM_CATEGORY__FILM_ID: &'static str = "film_id";
 pub const JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID: &'static str = "category_id";
+
+pub const JOIN_TABLE__INVENTORY: &str = "inventory";
+pub const JOIN_TABLE__INVENTORY__FILM_ID: &str = "film_id";
+pub const JOIN_TABLE__INVENTORY__STORE_ID: &str = "store_id";
@@ -1,10 +1,30 @@
 mod actor;
+mod address;
 mod category;
+mod city;
+mod country;
+mod customer;
 mod film;
-mod join_tables;
+mod film_aggregate;
+mod inventory;
+pub mod join_tables;
 mod language;
+mod payment;
+mod rental;
+mod staff;
+mod store;
 
-pub use actor::{Actor, ActorFilter};
-pub use category::{Category, CategoryFilter};
-pub use film::{Film, FilmFilter};
+pub use actor::{Actor, ActorFilter, ActorOrder};
+pub use address::{Address, AddressFilter};
+pub use category::{Category, CategoryFilter, CategoryOrder};
+pub use city::{City, CityFilter};
+pub use country::{Country, CountryFilter};
+pub use customer::{Customer, CustomerFilter, CustomerOrder};
+pub use film::{Film, FilmFilter, FilmOrder, MpaaRating};
+pub use film_aggregate::{FilmAggregate, FilmGroupBy};
+pub use inventory::Inventory;
 pub use language::{Language, LanguageFilter};
+pub use payment::{Payment, PaymentFilter, PaymentOrder};
+pub use rental::{Rental, RentalFilter, RentalOrder};
+pub use staff::Staff;
+pub use store::{Store, StoreFilter};
@@ -0,0 +1,235 @@
+use crate::graphql::core::filter::{
+    and_filters, eq, gt, gte, join_column, lt, lte, not_filter, or_filters, InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow};
+use crate::graphql::core::order::{NullsOrder, OrderDirection, OrderInput, SortKey};
+use crate::graphql::loader::StaffLoader;
+use crate::graphql::model::{Customer, CustomerFilter, Rental, RentalFilter, Staff};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SimpleObject};
+use rust_decimal::Decimal;
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use time::OffsetDateTime;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Payment {
+    pub payment_id: i32,
+
+    #[graphql(skip)]
+    pub customer_id: i32,
+
+    #[graphql(skip)]
+    pub staff_id: i32,
+
+    #[graphql(skip)]
+    pub rental_id: i32,
+
+    pub amount: Decimal,
+    pub payment_date: OffsetDateTime,
+}
+
+impl Default for Payment {
+    fn default() -> Self {
+        Self {
+            payment_id: 0,
+            customer_id: 0,
+            staff_id: 0,
+            rental_id: 0,
+            amount: Decimal::ZERO,
+            payment_date: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Payment {
+    pub const COLUMN_PAYMENT_ID: &'static str = "payment_id";
+    pub const COLUMN_CUSTOMER_ID: &'static str = "customer_id";
+    pub const COLUMN_STAFF_ID: &'static str = "staff_id";
+    pub const COLUMN_RENTAL_ID: &'static str = "rental_id";
+    pub const COLUMN_AMOUNT: &'static str = "amount";
+    pub const COLUMN_PAYMENT_DATE: &'static str = "payment_date";
+
+    async fn staff<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Staff> {
+        let staff = ctx
+            .data_unchecked::<DataLoader<StaffLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Staff>(ctx, self.staff_id)?)
+            .await?;
+
+        referenced(staff, "staff", self.staff_id)
+    }
+}
+
+impl TypeInfo for Payment {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_PAYMENT_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("paymentId", Self::COLUMN_PAYMENT_ID);
+            map.insert("amount", Self::COLUMN_AMOUNT);
+            map.insert("paymentDate", Self::COLUMN_PAYMENT_DATE);
+            map.insert("staff", Self::COLUMN_STAFF_ID);
+            map
+        });
+}
+
+impl FromRow for Payment {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_PAYMENT_ID,
+        Self::COLUMN_CUSTOMER_ID,
+        Self::COLUMN_STAFF_ID,
+        Self::COLUMN_RENTAL_ID,
+        Self::COLUMN_AMOUNT,
+        Self::COLUMN_PAYMENT_DATE,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_PAYMENT_ID => {
+                    result.payment_id = row.try_get(Self::COLUMN_PAYMENT_ID)?
+                }
+                Self::COLUMN_CUSTOMER_ID => {
+                    result.customer_id = row.try_get(Self::COLUMN_CUSTOMER_ID)?
+                }
+                Self::COLUMN_STAFF_ID => result.staff_id = row.try_get(Self::COLUMN_STAFF_ID)?,
+                Self::COLUMN_RENTAL_ID => result.rental_id = row.try_get(Self::COLUMN_RENTAL_ID)?,
+                Self::COLUMN_AMOUNT => result.amount = row.try_get(Self::COLUMN_AMOUNT)?,
+                Self::COLUMN_PAYMENT_DATE => {
+                    result.payment_date = row.try_get(Self::COLUMN_PAYMENT_DATE)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct PaymentFilter {
+    pub and: Option<Vec<PaymentFilter>>,
+    pub or: Option<Vec<PaymentFilter>>,
+    pub not: Option<Box<PaymentFilter>>,
+
+    pub customer: Option<Box<CustomerFilter>>,
+    pub rental: Option<Box<RentalFilter>>,
+
+    pub amount_eq: Option<Decimal>,
+    pub amount_gte: Option<Decimal>,
+    pub amount_lte: Option<Decimal>,
+
+    pub payment_date_gt: Option<OffsetDateTime>,
+    pub payment_date_gte: Option<OffsetDateTime>,
+    pub payment_date_lt: Option<OffsetDateTime>,
+    pub payment_date_lte: Option<OffsetDateTime>,
+}
+
+impl InputFilter for PaymentFilter {
+    // the parent of the partitioned payment_p2022_* tables - postgres routes the queries to them
+    const TABLE_NAME: &'static str = "payment";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.or.as_deref()
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.and.as_deref()
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        self.not.as_deref()
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, Payment::COLUMN_AMOUNT, &self.amount_eq);
+        gte(&mut g, Payment::COLUMN_AMOUNT, &self.amount_gte);
+        lte(&mut g, Payment::COLUMN_AMOUNT, &self.amount_lte);
+
+        gt(&mut g, Payment::COLUMN_PAYMENT_DATE, &self.payment_date_gt);
+        gte(&mut g, Payment::COLUMN_PAYMENT_DATE, &self.payment_date_gte);
+        lt(&mut g, Payment::COLUMN_PAYMENT_DATE, &self.payment_date_lt);
+        lte(&mut g, Payment::COLUMN_PAYMENT_DATE, &self.payment_date_lte);
+
+        join_column(
+            &mut g,
+            &self.customer,
+            Payment::COLUMN_CUSTOMER_ID,
+            Customer::COLUMN_CUSTOMER_ID,
+        );
+        join_column(
+            &mut g,
+            &self.rental,
+            Payment::COLUMN_RENTAL_ID,
+            Rental::COLUMN_RENTAL_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum PaymentOrderField {
+    Amount,
+    PaymentDate,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct PaymentOrder {
+    pub field: PaymentOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl OrderInput for PaymentOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            PaymentOrderField::Amount => SortKey::Field("amount"),
+            PaymentOrderField::PaymentDate => SortKey::Field("paymentDate"),
+        }
+    }
+
+    fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
+    }
+}
@@ -0,0 +1,267 @@
+use crate::config::QueryConfig;
+use crate::graphql::core::filter::{
+    and_filters, gt, gte, join_column, lt, lte, not_filter, null_check, or_filters, InputFilter,
+    TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::core::order::{NullsOrder, OrderDirection, OrderInput, SortKey};
+use crate::graphql::loader::{InventoryLoader, RentalPaymentLoader};
+use crate::graphql::model::{Customer, CustomerFilter, Inventory, Payment};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use time::OffsetDateTime;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Rental {
+    pub rental_id: i32,
+
+    #[graphql(skip)]
+    pub inventory_id: i32,
+
+    #[graphql(skip)]
+    pub customer_id: i32,
+
+    #[graphql(skip)]
+    pub staff_id: i32,
+
+    pub rental_date: OffsetDateTime,
+    pub return_date: Option<OffsetDateTime>,
+    pub last_update: OffsetDateTime,
+}
+
+impl Default for Rental {
+    fn default() -> Self {
+        Self {
+            rental_id: 0,
+            inventory_id: 0,
+            customer_id: 0,
+            staff_id: 0,
+            rental_date: OffsetDateTime::UNIX_EPOCH,
+            return_date: None,
+            last_update: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Rental {
+    pub const COLUMN_RENTAL_ID: &'static str = "rental_id";
+    pub const COLUMN_INVENTORY_ID: &'static str = "inventory_id";
+    pub const COLUMN_CUSTOMER_ID: &'static str = "customer_id";
+    pub const COLUMN_STAFF_ID: &'static str = "staff_id";
+    pub const COLUMN_RENTAL_DATE: &'static str = "rental_date";
+    pub const COLUMN_RETURN_DATE: &'static str = "return_date";
+    pub const COLUMN_LAST_UPDATE: &'static str = "last_update";
+
+    async fn inventory<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Inventory> {
+        let inventory = ctx
+            .data_unchecked::<DataLoader<InventoryLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Inventory>(ctx, self.inventory_id)?)
+            .await?;
+
+        referenced(inventory, "inventory", self.inventory_id)
+    }
+
+    async fn payments<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Vec<Payment>> {
+        let max_page_size = ctx.data_unchecked::<QueryConfig>().max_page_size;
+        let payments = ctx
+            .data_unchecked::<DataLoader<RentalPaymentLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(self.rental_id)
+            .await?;
+
+        Ok(payments
+            .unwrap_or_default()
+            .into_iter()
+            .take(max_page_size as usize)
+            .collect())
+    }
+}
+
+impl TypeInfo for Rental {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_RENTAL_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("rentalId", Self::COLUMN_RENTAL_ID);
+            map.insert("rentalDate", Self::COLUMN_RENTAL_DATE);
+            map.insert("returnDate", Self::COLUMN_RETURN_DATE);
+            map.insert("lastUpdate", Self::COLUMN_LAST_UPDATE);
+            map.insert("inventory", Self::COLUMN_INVENTORY_ID);
+            map.insert("payments", Self::COLUMN_RENTAL_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Rental {
+    fn id(&self) -> &i32 {
+        &self.rental_id
+    }
+}
+
+impl FromRow for Rental {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_RENTAL_ID,
+        Self::COLUMN_INVENTORY_ID,
+        Self::COLUMN_CUSTOMER_ID,
+        Self::COLUMN_STAFF_ID,
+        Self::COLUMN_RENTAL_DATE,
+        Self::COLUMN_RETURN_DATE,
+        Self::COLUMN_LAST_UPDATE,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_RENTAL_ID => result.rental_id = row.try_get(Self::COLUMN_RENTAL_ID)?,
+                Self::COLUMN_INVENTORY_ID => {
+                    result.inventory_id = row.try_get(Self::COLUMN_INVENTORY_ID)?
+                }
+                Self::COLUMN_CUSTOMER_ID => {
+                    result.customer_id = row.try_get(Self::COLUMN_CUSTOMER_ID)?
+                }
+                Self::COLUMN_STAFF_ID => result.staff_id = row.try_get(Self::COLUMN_STAFF_ID)?,
+                Self::COLUMN_RENTAL_DATE => {
+                    result.rental_date = row.try_get(Self::COLUMN_RENTAL_DATE)?
+                }
+                Self::COLUMN_RETURN_DATE => {
+                    result.return_date = row.try_get(Self::COLUMN_RETURN_DATE)?
+                }
+                Self::COLUMN_LAST_UPDATE => {
+                    result.last_update = row.try_get(Self::COLUMN_LAST_UPDATE)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct RentalFilter {
+    pub and: Option<Vec<RentalFilter>>,
+    pub or: Option<Vec<RentalFilter>>,
+    pub not: Option<Box<RentalFilter>>,
+
+    pub customer: Option<Box<CustomerFilter>>,
+
+    pub rental_date_gt: Option<OffsetDateTime>,
+    pub rental_date_gte: Option<OffsetDateTime>,
+    pub rental_date_lt: Option<OffsetDateTime>,
+    pub rental_date_lte: Option<OffsetDateTime>,
+
+    pub return_date_gt: Option<OffsetDateTime>,
+    pub return_date_gte: Option<OffsetDateTime>,
+    pub return_date_lt: Option<OffsetDateTime>,
+    pub return_date_lte: Option<OffsetDateTime>,
+    pub return_date_is_null: Option<bool>,
+}
+
+impl InputFilter for RentalFilter {
+    const TABLE_NAME: &'static str = "rental";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.or.as_deref()
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        self.and.as_deref()
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        self.not.as_deref()
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        gt(&mut g, Rental::COLUMN_RENTAL_DATE, &self.rental_date_gt);
+        gte(&mut g, Rental::COLUMN_RENTAL_DATE, &self.rental_date_gte);
+        lt(&mut g, Rental::COLUMN_RENTAL_DATE, &self.rental_date_lt);
+        lte(&mut g, Rental::COLUMN_RENTAL_DATE, &self.rental_date_lte);
+
+        gt(&mut g, Rental::COLUMN_RETURN_DATE, &self.return_date_gt);
+        gte(&mut g, Rental::COLUMN_RETURN_DATE, &self.return_date_gte);
+        lt(&mut g, Rental::COLUMN_RETURN_DATE, &self.return_date_lt);
+        lte(&mut g, Rental::COLUMN_RETURN_DATE, &self.return_date_lte);
+        null_check(
+            &mut g,
+            Rental::COLUMN_RETURN_DATE,
+            &self.return_date_is_null,
+        );
+
+        join_column(
+            &mut g,
+            &self.customer,
+            Rental::COLUMN_CUSTOMER_ID,
+            Customer::COLUMN_CUSTOMER_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum RentalOrderField {
+    RentalDate,
+    ReturnDate,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RentalOrder {
+    pub field: RentalOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl OrderInput for RentalOrder {
+    fn key(&self) -> SortKey {
+        match self.field {
+            RentalOrderField::RentalDate => SortKey::Field("rentalDate"),
+            RentalOrderField::ReturnDate => SortKey::Field("returnDate"),
+        }
+    }
+
+    fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    fn nulls(&self) -> Option<NullsOrder> {
+        self.nulls
+    }
+}
@@ -0,0 +1,126 @@
+use crate::graphql::core::filter::TypeInfo;
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::loader::{AddressLoader, StoreLoader};
+use crate::graphql::model::{Address, Store};
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct Staff {
+    pub staff_id: i32,
+
+    #[graphql(skip)]
+    pub store_id: i32,
+
+    #[graphql(skip)]
+    pub address_id: i32,
+
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub active: bool,
+    pub username: String,
+}
+
+#[ComplexObject]
+impl Staff {
+    pub const COLUMN_STAFF_ID: &'static str = "staff_id";
+    pub const COLUMN_STORE_ID: &'static str = "store_id";
+    pub const COLUMN_ADDRESS_ID: &'static str = "address_id";
+    pub const COLUMN_FIRST_NAME: &'static str = "first_name";
+    pub const COLUMN_LAST_NAME: &'static str = "last_name";
+    pub const COLUMN_EMAIL: &'static str = "email";
+    pub const COLUMN_ACTIVE: &'static str = "active";
+    pub const COLUMN_USERNAME: &'static str = "username";
+    pub const TABLE_NAME: &'static str = "staff";
+
+    async fn store<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Store> {
+        let store = ctx
+            .data_unchecked::<DataLoader<StoreLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Store>(ctx, self.store_id)?)
+            .await?;
+
+        referenced(store, "store", self.store_id)
+    }
+
+    async fn address<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Address> {
+        let address = ctx
+            .data_unchecked::<DataLoader<AddressLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Address>(ctx, self.address_id)?)
+            .await?;
+
+        referenced(address, "address", self.address_id)
+    }
+}
+
+impl TypeInfo for Staff {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_STAFF_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("staffId", Self::COLUMN_STAFF_ID);
+            map.insert("firstName", Self::COLUMN_FIRST_NAME);
+            map.insert("lastName", Self::COLUMN_LAST_NAME);
+            map.insert("email", Self::COLUMN_EMAIL);
+            map.insert("active", Self::COLUMN_ACTIVE);
+            map.insert("username", Self::COLUMN_USERNAME);
+            map.insert("store", Self::COLUMN_STORE_ID);
+            map.insert("address", Self::COLUMN_ADDRESS_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Staff {
+    fn id(&self) -> &i32 {
+        &self.staff_id
+    }
+}
+
+impl FromRow for Staff {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_STAFF_ID,
+        Self::COLUMN_STORE_ID,
+        Self::COLUMN_ADDRESS_ID,
+        Self::COLUMN_FIRST_NAME,
+        Self::COLUMN_LAST_NAME,
+        Self::COLUMN_EMAIL,
+        Self::COLUMN_ACTIVE,
+        Self::COLUMN_USERNAME,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_STAFF_ID => result.staff_id = row.try_get(Self::COLUMN_STAFF_ID)?,
+                Self::COLUMN_STORE_ID => result.store_id = row.try_get(Self::COLUMN_STORE_ID)?,
+                Self::COLUMN_ADDRESS_ID => {
+                    result.address_id = row.try_get(Self::COLUMN_ADDRESS_ID)?
+                }
+                Self::COLUMN_FIRST_NAME => {
+                    result.first_name = row.try_get(Self::COLUMN_FIRST_NAME)?
+                }
+                Self::COLUMN_LAST_NAME => result.last_name = row.try_get(Self::COLUMN_LAST_NAME)?,
+                Self::COLUMN_EMAIL => result.email = row.try_get(Self::COLUMN_EMAIL)?,
+                Self::COLUMN_ACTIVE => result.active = row.try_get(Self::COLUMN_ACTIVE)?,
+                Self::COLUMN_USERNAME => result.username = row.try_get(Self::COLUMN_USERNAME)?,
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::graphql::core::filter::{
+    and_filters, eq, is_in, join_column, not_filter, or_filters, InputFilter, TypeInfo,
+};
+use crate::graphql::core::loader::{referenced, ColumnsKey, FromRow, WithId};
+use crate::graphql::loader::{AddressLoader, StaffLoader};
+use crate::graphql::model::{Address, AddressFilter, Staff};
+use crate::query::{Combinator, FilterGroup};
+use crate::util::MaybeOwned;
+use anyhow::Error;
+use async_graphql::dataloader::{DataLoader, HashMapCache};
+use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use std::sync::LazyLock;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+#[graphql(complex)]
+pub struct Store {
+    pub store_id: i32,
+
+    #[graphql(skip)]
+    pub manager_staff_id: i32,
+
+    #[graphql(skip)]
+    pub address_id: i32,
+}
+
+#[ComplexObject]
+impl Store {
+    pub const COLUMN_STORE_ID: &'static str = "store_id";
+    pub const COLUMN_MANAGER_STAFF_ID: &'static str = "manager_staff_id";
+    pub const COLUMN_ADDRESS_ID: &'static str = "address_id";
+
+    async fn manager<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Staff> {
+        let manager = ctx
+            .data_unchecked::<DataLoader<StaffLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Staff>(ctx, self.manager_staff_id)?)
+            .await?;
+
+        referenced(manager, "staff", self.manager_staff_id)
+    }
+
+    async fn address<'a>(&self, ctx: &Context<'a>) -> async_graphql::Result<Address> {
+        let address = ctx
+            .data_unchecked::<DataLoader<AddressLoader, HashMapCache<FxBuildHasher>>>()
+            .load_one(ColumnsKey::new::<Address>(ctx, self.address_id)?)
+            .await?;
+
+        referenced(address, "address", self.address_id)
+    }
+}
+
+impl TypeInfo for Store {
+    const PRIMARY_KEY: &'static str = Self::COLUMN_STORE_ID;
+
+    const QUERY_FIELD_TO_DB_COLUMN_MAP: LazyLock<FxHashMap<&'static str, &'static str>> =
+        LazyLock::new(|| {
+            let mut map = FxHashMap::default();
+            map.insert("storeId", Self::COLUMN_STORE_ID);
+            map.insert("manager", Self::COLUMN_MANAGER_STAFF_ID);
+            map.insert("address", Self::COLUMN_ADDRESS_ID);
+            map
+        });
+}
+
+impl WithId<i32> for Store {
+    fn id(&self) -> &i32 {
+        &self.store_id
+    }
+}
+
+impl FromRow for Store {
+    const COLUMNS: &'static [&'static str] = &[
+        Self::COLUMN_STORE_ID,
+        Self::COLUMN_MANAGER_STAFF_ID,
+        Self::COLUMN_ADDRESS_ID,
+    ];
+
+    fn from_columns(row: &Row, columns: &[&str]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut result = Self::default();
+        for &column in columns {
+            match column {
+                Self::COLUMN_STORE_ID => result.store_id = row.try_get(Self::COLUMN_STORE_ID)?,
+                Self::COLUMN_MANAGER_STAFF_ID => {
+                    result.manager_staff_id = row.try_get(Self::COLUMN_MANAGER_STAFF_ID)?
+                }
+                Self::COLUMN_ADDRESS_ID => {
+                    result.address_id = row.try_get(Self::COLUMN_ADDRESS_ID)?
+                }
+                column => anyhow::bail!(
+                    "the column '{}' is not mapped to a field of '{}'",
+                    column,
+                    std::any::type_name::<Self>()
+                ),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct StoreFilter {
+    pub address: Option<Box<AddressFilter>>,
+
+    pub store_id_eq: Option<i32>,
+    pub store_id_in: Option<Vec<i32>>,
+}
+
+impl InputFilter for StoreFilter {
+    const TABLE_NAME: &'static str = "store";
+
+    fn or_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn and_filters(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn not_filter(&self) -> Option<&Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn collect_into<'f>(&'f self, collector: &mut FilterGroup<'f>) {
+        let mut g = match collector.combinator() == Combinator::And {
+            true => MaybeOwned::Borrowed(collector),
+            false => MaybeOwned::Owned(FilterGroup::new(Combinator::And)),
+        };
+
+        and_filters(&mut g, self);
+        or_filters(&mut g, self);
+        not_filter(&mut g, self);
+
+        eq(&mut g, Store::COLUMN_STORE_ID, &self.store_id_eq);
+        is_in(&mut g, Store::COLUMN_STORE_ID, &self.store_id_in);
+
+        join_column(
+            &mut g,
+            &self.address,
+            Store::COLUMN_ADDRESS_ID,
+            Address::COLUMN_ADDRESS_ID,
+        );
+
+        if let MaybeOwned::Owned(g) = g {
+            collector.add_group(g);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::FromRow;
+use crate::graphql::core::mutation::{lock_version, returned_columns, Assignments, MutationError};
+use crate::graphql::model::join_tables::{
+    JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+};
+use crate::graphql::model::{Actor, ActorFilter};
+use async_graphql::{Context, ErrorExtensions, InputObject, Object};
+use deadpool_postgres::Pool;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateActorInput {
+    #[graphql(validator(min_length = 1, max_length = 45))]
+    pub first_name: String,
+    #[graphql(validator(min_length = 1, max_length = 45))]
+    pub last_name: String,
+}
+
+// the fields, which are not set, keep their current values
+#[derive(Debug, Clone, InputObject)]
+pub struct UpdateActorInput {
+    #[graphql(validator(min_length = 1, max_length = 45))]
+    pub first_name: Option<String>,
+    #[graphql(validator(min_length = 1, max_length = 45))]
+    pub last_name: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ActorMutation;
+
+#[Object]
+impl ActorMutation {
+    async fn create_actor<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: CreateActorInput,
+    ) -> async_graphql::Result<Actor> {
+        let mut assignments = Assignments::default();
+        assignments.set(Actor::COLUMN_FIRST_NAME, &input.first_name);
+        assignments.set(Actor::COLUMN_LAST_NAME, &input.last_name);
+
+        let (sql, params) = assignments.insert::<Actor>(ActorFilter::TABLE_NAME);
+        tracing::debug!(query=sql, paramters=?params, table=ActorFilter::TABLE_NAME);
+
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        let stm = tx.prepare_cached(&sql).await?;
+        let row = tx.query_one(&stm, &params).await?;
+        let actor = Actor::from_row(&row)?;
+
+        tx.commit().await?;
+        Ok(actor)
+    }
+
+    async fn update_actor<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: i32,
+        input: UpdateActorInput,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Actor> {
+        let mut assignments = Assignments::default();
+        assignments.set_opt(Actor::COLUMN_FIRST_NAME, &input.first_name);
+        assignments.set_opt(Actor::COLUMN_LAST_NAME, &input.last_name);
+
+        let (sql, params) = assignments.update::<Actor>(ActorFilter::TABLE_NAME, &id);
+        tracing::debug!(query=sql, paramters=?params, table=ActorFilter::TABLE_NAME);
+
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+        lock_version::<Actor>(
+            &tx,
+            ActorFilter::TABLE_NAME,
+            "actor",
+            id,
+            expected_last_update,
+        )
+        .await?;
+
+        let stm = tx.prepare_cached(&sql).await?;
+        let row = tx.query_one(&stm, &params).await?;
+        let actor = Actor::from_row(&row)?;
+
+        tx.commit().await?;
+        Ok(actor)
+    }
+
+    // Deleting an actor, who still plays in films, requires `cascade` to also
+    // remove the actor from those films
+    async fn delete_actor<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: i32,
+        #[graphql(default)] cascade: bool,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Actor> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        // the lock keeps new films from referencing the actor until the commit
+        lock_version::<Actor>(
+            &tx,
+            ActorFilter::TABLE_NAME,
+            "actor",
+            id,
+            expected_last_update,
+        )
+        .await?;
+
+        let count = format!(
+            "SELECT count(*) FROM {} WHERE {} = $1",
+            JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+        );
+        let stm = tx.prepare_cached(&count).await?;
+        let films: i64 = tx.query_one(&stm, &[&id]).await?.try_get(0)?;
+
+        if films > 0 {
+            if !cascade {
+                let error = MutationError::HasDependents {
+                    entity: "actor",
+                    id,
+                    dependents: "films",
+                    count: films,
+                };
+                return Err(error.extend());
+            }
+
+            let unlink = format!(
+                "DELETE FROM {} WHERE {} = $1",
+                JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+            );
+            tracing::debug!(query = unlink, table = JOIN_TABLE__FILM_ACTOR);
+            let stm = tx.prepare_cached(&unlink).await?;
+            tx.execute(&stm, &[&id]).await?;
+        }
+
+        let delete = format!(
+            "DELETE FROM {} WHERE {} = $1 RETURNING {}",
+            ActorFilter::TABLE_NAME,
+            Actor::COLUMN_ACTOR_ID,
+            returned_columns::<Actor>(),
+        );
+        tracing::debug!(query = delete, table = ActorFilter::TABLE_NAME);
+        let stm = tx.prepare_cached(&delete).await?;
+        let row = tx.query_one(&stm, &[&id]).await?;
+        let actor = Actor::from_row(&row)?;
+
+        tx.commit().await?;
+        Ok(actor)
+    }
+}
@@ -0,0 +1,380 @@
+use crate::graphql::core::filter::{InputFilter, TypeInfo};
+use crate::graphql::core::loader::FromRow;
+use crate::graphql::core::mutation::{lock_version, missing_ids, Assignments, MutationError};
+use crate::graphql::model::join_tables::{
+    JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__ACTOR_ID, JOIN_TABLE__FILM_ACTOR__FILM_ID,
+    JOIN_TABLE__FILM_CATEGORY, JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+    JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+};
+use crate::graphql::model::{
+    Actor, ActorFilter, Category, CategoryFilter, Film, FilmFilter, Language, LanguageFilter,
+    MpaaRating,
+};
+use crate::query::{Page, SqlVisitor, TableFilter};
+use async_graphql::{Context, ErrorExtensions, InputObject, Object};
+use deadpool_postgres::{Pool, Transaction};
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateFilmInput {
+    #[graphql(validator(min_length = 1))]
+    pub title: String,
+    pub description: String,
+    #[graphql(validator(minimum = 1))]
+    pub length: i16,
+    #[graphql(validator(minimum = 1901, maximum = 2155))]
+    pub release_year: Option<i32>,
+    // must match exactly one language, e.g. `{ languageIdEq: 1 }` or `{ nameEq: "English" }`
+    pub language: LanguageFilter,
+    pub original_language: Option<LanguageFilter>,
+    #[graphql(validator(minimum = 1))]
+    pub rental_duration: Option<i16>,
+    pub rental_rate: Option<Decimal>,
+    pub replacement_cost: Option<Decimal>,
+    pub rating: Option<MpaaRating>,
+    pub special_features: Option<Vec<String>>,
+    #[graphql(default)]
+    pub actor_ids: Vec<i32>,
+    #[graphql(default)]
+    pub category_ids: Vec<i32>,
+}
+
+// the fields, which are not set, keep their current values
+#[derive(Debug, Clone, InputObject)]
+pub struct UpdateFilmInput {
+    #[graphql(validator(min_length = 1))]
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[graphql(validator(minimum = 1))]
+    pub length: Option<i16>,
+    #[graphql(validator(minimum = 1901, maximum = 2155))]
+    pub release_year: Option<i32>,
+    pub language: Option<LanguageFilter>,
+    pub original_language: Option<LanguageFilter>,
+    #[graphql(validator(minimum = 1))]
+    pub rental_duration: Option<i16>,
+    pub rental_rate: Option<Decimal>,
+    pub replacement_cost: Option<Decimal>,
+    pub rating: Option<MpaaRating>,
+    pub special_features: Option<Vec<String>>,
+}
+
+#[derive(Default)]
+pub struct FilmMutation;
+
+#[Object]
+impl FilmMutation {
+    async fn create_film<'a>(
+        &self,
+        ctx: &Context<'a>,
+        input: CreateFilmInput,
+    ) -> async_graphql::Result<Film> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        let language_id = resolve_language(&tx, &input.language).await?;
+        let original_language_id = match &input.original_language {
+            Some(filter) => Some(resolve_language(&tx, filter).await?),
+            None => None,
+        };
+
+        let mut assignments = Assignments::default();
+        assignments.set(Film::COLUMN_TITLE, &input.title);
+        assignments.set(Film::COLUMN_DESCRIPTION, &input.description);
+        assignments.set(Film::COLUMN_LENGTH, &input.length);
+        assignments.set(Film::COLUMN_LANGUAGE_ID, &language_id);
+        assignments.set_opt(Film::COLUMN_ORIGINAL_LANGUAGE_ID, &original_language_id);
+        set_film_fields(
+            &mut assignments,
+            &input.release_year,
+            &input.rental_duration,
+            &input.rental_rate,
+            &input.replacement_cost,
+            &input.rating,
+            &input.special_features,
+        );
+
+        let (sql, params) = assignments.insert::<Film>(FilmFilter::TABLE_NAME);
+        tracing::debug!(query=sql, paramters=?params, table=FilmFilter::TABLE_NAME);
+
+        let stm = tx.prepare_cached(&sql).await?;
+        let row = tx.query_one(&stm, &params).await?;
+        let film = Film::from_row(&row)?;
+
+        add_actors(&tx, film.film_id, &input.actor_ids).await?;
+        set_categories(&tx, film.film_id, &input.category_ids).await?;
+
+        tx.commit().await?;
+        Ok(film)
+    }
+
+    async fn update_film<'a>(
+        &self,
+        ctx: &Context<'a>,
+        id: i32,
+        input: UpdateFilmInput,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Film> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+        lock_version::<Film>(
+            &tx,
+            FilmFilter::TABLE_NAME,
+            "film",
+            id,
+            expected_last_update,
+        )
+        .await?;
+
+        let language_id = match &input.language {
+            Some(filter) => Some(resolve_language(&tx, filter).await?),
+            None => None,
+        };
+        let original_language_id = match &input.original_language {
+            Some(filter) => Some(resolve_language(&tx, filter).await?),
+            None => None,
+        };
+
+        let mut assignments = Assignments::default();
+        assignments.set_opt(Film::COLUMN_TITLE, &input.title);
+        assignments.set_opt(Film::COLUMN_DESCRIPTION, &input.description);
+        assignments.set_opt(Film::COLUMN_LENGTH, &input.length);
+        assignments.set_opt(Film::COLUMN_LANGUAGE_ID, &language_id);
+        assignments.set_opt(Film::COLUMN_ORIGINAL_LANGUAGE_ID, &original_language_id);
+        set_film_fields(
+            &mut assignments,
+            &input.release_year,
+            &input.rental_duration,
+            &input.rental_rate,
+            &input.replacement_cost,
+            &input.rating,
+            &input.special_features,
+        );
+
+        let (sql, params) = assignments.update::<Film>(FilmFilter::TABLE_NAME, &id);
+        tracing::debug!(query=sql, paramters=?params, table=FilmFilter::TABLE_NAME);
+
+        let stm = tx.prepare_cached(&sql).await?;
+        let row = tx.query_one(&stm, &params).await?;
+        let film = Film::from_row(&row)?;
+
+        tx.commit().await?;
+        Ok(film)
+    }
+
+    async fn add_actors_to_film<'a>(
+        &self,
+        ctx: &Context<'a>,
+        film_id: i32,
+        actor_ids: Vec<i32>,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Film> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        lock_version::<Film>(
+            &tx,
+            FilmFilter::TABLE_NAME,
+            "film",
+            film_id,
+            expected_last_update,
+        )
+        .await?;
+        add_actors(&tx, film_id, &actor_ids).await?;
+        let film = touch_film(&tx, film_id).await?;
+
+        tx.commit().await?;
+        Ok(film)
+    }
+
+    async fn remove_actors_from_film<'a>(
+        &self,
+        ctx: &Context<'a>,
+        film_id: i32,
+        actor_ids: Vec<i32>,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Film> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        lock_version::<Film>(
+            &tx,
+            FilmFilter::TABLE_NAME,
+            "film",
+            film_id,
+            expected_last_update,
+        )
+        .await?;
+
+        let sql = format!(
+            "DELETE FROM {} WHERE {} = $1 AND {} = ANY ($2)",
+            JOIN_TABLE__FILM_ACTOR,
+            JOIN_TABLE__FILM_ACTOR__FILM_ID,
+            JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+        );
+        tracing::debug!(query = sql, table = JOIN_TABLE__FILM_ACTOR);
+        let stm = tx.prepare_cached(&sql).await?;
+        tx.execute(&stm, &[&film_id, &actor_ids]).await?;
+        let film = touch_film(&tx, film_id).await?;
+
+        tx.commit().await?;
+        Ok(film)
+    }
+
+    // Replaces the categories of the film with the given ones
+    async fn set_film_categories<'a>(
+        &self,
+        ctx: &Context<'a>,
+        film_id: i32,
+        category_ids: Vec<i32>,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Film> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        lock_version::<Film>(
+            &tx,
+            FilmFilter::TABLE_NAME,
+            "film",
+            film_id,
+            expected_last_update,
+        )
+        .await?;
+        set_categories(&tx, film_id, &category_ids).await?;
+        let film = touch_film(&tx, film_id).await?;
+
+        tx.commit().await?;
+        Ok(film)
+    }
+}
+
+// The columns, which are optional for both the creation and the update of a film
+fn set_film_fields<'a>(
+    assignments: &mut Assignments<'a>,
+    release_year: &'a Option<i32>,
+    rental_duration: &'a Option<i16>,
+    rental_rate: &'a Option<Decimal>,
+    replacement_cost: &'a Option<Decimal>,
+    rating: &'a Option<MpaaRating>,
+    special_features: &'a Option<Vec<String>>,
+) {
+    assignments.set_opt_as(Film::COLUMN_RELEASE_YEAR, release_year, "integer");
+    assignments.set_opt(Film::COLUMN_RENTAL_DURATION, rental_duration);
+    assignments.set_opt(Film::COLUMN_RENTAL_RATE, rental_rate);
+    assignments.set_opt(Film::COLUMN_REPLACEMENT_COST, replacement_cost);
+    assignments.set_opt(Film::COLUMN_RATING, rating);
+    assignments.set_opt(Film::COLUMN_SPECIAL_FEATURES, special_features);
+}
+
+// The id of the only language matching the filter
+async fn resolve_language(
+    tx: &Transaction<'_>,
+    filter: &LanguageFilter,
+) -> async_graphql::Result<i32> {
+    let mut tf = TableFilter::new(LanguageFilter::TABLE_NAME);
+    filter.collect_into(tf.filter_group_mut());
+
+    // a second match is enough to tell that the filter is ambiguous
+    let limit = 2i64;
+    let (sql, params) = SqlVisitor::new()
+        .paginate(Page {
+            limit: &limit,
+            offset: None,
+        })
+        .translate(&tf, &[Language::PRIMARY_KEY]);
+    tracing::debug!(query=sql, paramters=?params, table=LanguageFilter::TABLE_NAME);
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let rows = tx.query(&stm, &params).await?;
+    match rows.as_slice() {
+        [row] => Ok(row.try_get(Language::PRIMARY_KEY)?),
+        [] => Err(async_graphql::Error::new("no language matches the filter")),
+        _ => Err(async_graphql::Error::new(
+            "more than one language matches the filter",
+        )),
+    }
+}
+
+async fn add_actors(
+    tx: &Transaction<'_>,
+    film_id: i32,
+    actor_ids: &[i32],
+) -> async_graphql::Result<()> {
+    if actor_ids.is_empty() {
+        return Ok(());
+    }
+
+    let missing = missing_ids::<Actor>(tx, ActorFilter::TABLE_NAME, actor_ids).await?;
+    if !missing.is_empty() {
+        let error = MutationError::NotFoundMany {
+            entity: "actor",
+            ids: missing,
+        };
+        return Err(error.extend());
+    }
+
+    let sql = format!(
+        "INSERT INTO {} ({}, {}) SELECT $1, unnest($2::int4[]) ON CONFLICT DO NOTHING",
+        JOIN_TABLE__FILM_ACTOR, JOIN_TABLE__FILM_ACTOR__FILM_ID, JOIN_TABLE__FILM_ACTOR__ACTOR_ID,
+    );
+    tracing::debug!(query = sql, table = JOIN_TABLE__FILM_ACTOR);
+    let stm = tx.prepare_cached(&sql).await?;
+    tx.execute(&stm, &[&film_id, &actor_ids]).await?;
+
+    Ok(())
+}
+
+async fn set_categories(
+    tx: &Transaction<'_>,
+    film_id: i32,
+    category_ids: &[i32],
+) -> async_graphql::Result<()> {
+    let missing = missing_ids::<Category>(tx, CategoryFilter::TABLE_NAME, category_ids).await?;
+    if !missing.is_empty() {
+        let error = MutationError::NotFoundMany {
+            entity: "category",
+            ids: missing,
+        };
+        return Err(error.extend());
+    }
+
+    let unlink = format!(
+        "DELETE FROM {} WHERE {} = $1 AND NOT ({} = ANY ($2))",
+        JOIN_TABLE__FILM_CATEGORY,
+        JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+        JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+    );
+    tracing::debug!(query = unlink, table = JOIN_TABLE__FILM_CATEGORY);
+    let stm = tx.prepare_cached(&unlink).await?;
+    tx.execute(&stm, &[&film_id, &category_ids]).await?;
+
+    let link = format!(
+        "INSERT INTO {} ({}, {}) SELECT $1, unnest($2::int4[]) ON CONFLICT DO NOTHING",
+        JOIN_TABLE__FILM_CATEGORY,
+        JOIN_TABLE__FILM_CATEGORY__FILM_ID,
+        JOIN_TABLE__FILM_CATEGORY__CATEGORY_ID,
+    );
+    tracing::debug!(query = link, table = JOIN_TABLE__FILM_CATEGORY);
+    let stm = tx.prepare_cached(&link).await?;
+    tx.execute(&stm, &[&film_id, &category_ids]).await?;
+
+    Ok(())
+}
+
+// The actors and categories are part of the version of the film, so changing
+// them bumps its `last_update` (set by every update), which is checked by
+// `lock_version`
+async fn touch_film(tx: &Transaction<'_>, id: i32) -> async_graphql::Result<Film> {
+    let (sql, params) = Assignments::default().update::<Film>(FilmFilter::TABLE_NAME, &id);
+    tracing::debug!(query=sql, paramters=?params, table=FilmFilter::TABLE_NAME);
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let row = tx.query_one(&stm, &params).await?;
+    Ok(Film::from_row(&row)?)
+}
@@ -0,0 +1,728 @@
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::model::{Film, FilmFilter, Language, LanguageFilter, MpaaRating};
+use async_graphql::{Context, Enum, Object, SimpleObject, Upload, UploadValue};
+use deadpool_postgres::{Pool, Transaction};
+use futures_util::pin_mut;
+use rust_decimal::Decimal;
+use rustc_hash::FxHashMap;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use tokio::sync::mpsc::{channel, Sender};
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
+use tokio_postgres::types::{ToSql, Type};
+
+// The number of parsed records, which may wait for the COPY
+const RECORD_BUFFER: usize = 256;
+
+// The columns written by the import, in the order of `FilmRow::values()`
+const COLUMNS: [&str; 11] = [
+    Film::COLUMN_TITLE,
+    Film::COLUMN_DESCRIPTION,
+    Film::COLUMN_LENGTH,
+    Film::COLUMN_RELEASE_YEAR,
+    Film::COLUMN_LANGUAGE_ID,
+    Film::COLUMN_ORIGINAL_LANGUAGE_ID,
+    Film::COLUMN_RENTAL_DURATION,
+    Film::COLUMN_RENTAL_RATE,
+    Film::COLUMN_REPLACEMENT_COST,
+    Film::COLUMN_RATING,
+    Film::COLUMN_SPECIAL_FEATURES,
+];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+pub enum ImportFormat {
+    // comma separated values, the first row names the columns
+    Csv,
+    // one JSON object per line
+    Ndjson,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct FilmImportError {
+    // the line of the file, on which the rejected row starts
+    pub line: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct FilmImportResult {
+    pub imported: i32,
+    pub errors: Vec<FilmImportError>,
+}
+
+#[derive(Default)]
+pub struct FilmImportMutation;
+
+#[Object]
+impl FilmImportMutation {
+    // Imports the valid rows of the file and reports the rejected ones. When
+    // `format` is omitted, it is derived from the file extension or content type.
+    async fn import_films<'a>(
+        &self,
+        ctx: &Context<'a>,
+        file: Upload,
+        format: Option<ImportFormat>,
+    ) -> async_graphql::Result<FilmImportResult> {
+        let upload = file.value(ctx)?;
+        let Some(format) = format.or_else(|| detect_format(&upload)) else {
+            return Err(async_graphql::Error::new(format!(
+                "cannot detect the format of '{}', use the `format` argument",
+                upload.filename
+            )));
+        };
+
+        // the records are parsed on a blocking thread, while the valid ones
+        // are copied into the table
+        let (sender, mut receiver) = channel(RECORD_BUFFER);
+        let reader = BufReader::new(upload.into_read());
+        let parser = tokio::task::spawn_blocking(move || send_records(reader, format, sender));
+
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        let languages = load_languages(&tx).await?;
+
+        // the statement reports the types of the columns, as expected by the binary COPY
+        let select = format!(
+            "SELECT {} FROM {}",
+            COLUMNS.join(", "),
+            FilmFilter::TABLE_NAME
+        );
+        let stm = tx.prepare(&select).await?;
+        let types = stm
+            .columns()
+            .iter()
+            .map(|c| c.type_().clone())
+            .collect::<Vec<Type>>();
+
+        let copy = format!(
+            "COPY {} ({}) FROM STDIN BINARY",
+            FilmFilter::TABLE_NAME,
+            COLUMNS.join(", ")
+        );
+        tracing::debug!(query = copy, table = FilmFilter::TABLE_NAME);
+
+        let sink = tx.copy_in(&copy).await?;
+        let writer = BinaryCopyInWriter::new(sink, &types);
+        pin_mut!(writer);
+
+        // an unreadable file fails the import, the COPY is aborted together
+        // with the transaction
+        let mut errors = vec![];
+        while let Some(record) = receiver.recv().await {
+            let (line, record) = record?;
+            match record.and_then(|record| FilmRow::new(record, &languages)) {
+                Ok(row) => writer.as_mut().write(&row.values()).await?,
+                Err(message) => errors.push(FilmImportError {
+                    line: i32::try_from(line)?,
+                    message,
+                }),
+            }
+        }
+        parser.await?;
+
+        let imported = writer.finish().await?;
+        tx.commit().await?;
+
+        Ok(FilmImportResult {
+            imported: i32::try_from(imported)?,
+            errors,
+        })
+    }
+}
+
+// Sends the records until the end of the file, the first error reading it or
+// until the import stops receiving them
+fn send_records<R: BufRead>(
+    reader: R,
+    format: ImportFormat,
+    sender: Sender<std::io::Result<(usize, Result<FilmRecord, String>)>>,
+) {
+    let records = match Records::new(reader, format) {
+        Ok(records) => records,
+        Err(e) => {
+            let _ = sender.blocking_send(Err(e));
+            return;
+        }
+    };
+
+    for record in records {
+        let failed = record.is_err();
+        if sender.blocking_send(record).is_err() || failed {
+            return;
+        }
+    }
+}
+
+fn detect_format(upload: &UploadValue) -> Option<ImportFormat> {
+    let extension = upload
+        .filename
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+
+    match (extension.as_deref(), upload.content_type.as_deref()) {
+        (Some("csv"), _) | (_, Some("text/csv")) => Some(ImportFormat::Csv),
+        (Some("ndjson" | "jsonl"), _) | (_, Some("application/x-ndjson")) => {
+            Some(ImportFormat::Ndjson)
+        }
+        _ => None,
+    }
+}
+
+// The ids of the languages by their lowercase names
+async fn load_languages(tx: &Transaction<'_>) -> async_graphql::Result<FxHashMap<String, i32>> {
+    let sql = format!(
+        "SELECT {}, {} FROM {}",
+        Language::COLUMN_LANGUAGE_ID,
+        Language::COLUMN_NAME,
+        LanguageFilter::TABLE_NAME,
+    );
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let mut languages = FxHashMap::default();
+    for row in tx.query(&stm, &[]).await? {
+        let name: String = row.try_get(Language::COLUMN_NAME)?;
+        languages.insert(
+            name.trim().to_lowercase(),
+            row.try_get(Language::COLUMN_LANGUAGE_ID)?,
+        );
+    }
+
+    Ok(languages)
+}
+
+// A row of the imported file. The fields, which are not optional in the `Film`
+// model, are required.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilmRecord {
+    title: String,
+    description: String,
+    length: i64,
+    release_year: Option<i32>,
+    language: String,
+    original_language: Option<String>,
+    rental_duration: i64,
+    rental_rate: Decimal,
+    replacement_cost: Decimal,
+    rating: Option<MpaaRating>,
+    special_features: Option<Vec<String>>,
+}
+
+// A validated record, with its languages resolved to ids. The checks mirror
+// the constraints of the film table, so a row cannot fail the whole COPY.
+struct FilmRow {
+    record: FilmRecord,
+    length: i16,
+    rental_duration: i16,
+    language_id: i32,
+    original_language_id: Option<i32>,
+}
+
+impl FilmRow {
+    fn new(record: FilmRecord, languages: &FxHashMap<String, i32>) -> Result<Self, String> {
+        if record.title.trim().is_empty() {
+            return Err("the title must not be empty".to_owned());
+        }
+        // varchar(255)
+        let title_length = record.title.chars().count();
+        if title_length > 255 {
+            return Err(format!(
+                "the title has {} characters, but at most 255 are allowed",
+                title_length
+            ));
+        }
+        check_text("title", &record.title)?;
+        check_text("description", &record.description)?;
+        for feature in record.special_features.iter().flatten() {
+            check_text("special features", feature)?;
+        }
+
+        let length = positive_smallint("length", record.length)?;
+        let rental_duration = positive_smallint("rental duration", record.rental_duration)?;
+        if let Some(year) = record.release_year {
+            if !(1901..=2155).contains(&year) {
+                return Err(format!(
+                    "the release year {} is not between 1901 and 2155",
+                    year
+                ));
+            }
+        }
+        // numeric(4, 2) and numeric(5, 2)
+        check_amount("rental rate", record.rental_rate, Decimal::new(9999, 2))?;
+        check_amount(
+            "replacement cost",
+            record.replacement_cost,
+            Decimal::new(99999, 2),
+        )?;
+
+        let language_id = language(languages, &record.language)?;
+        let original_language_id = match &record.original_language {
+            Some(name) => Some(language(languages, name)?),
+            None => None,
+        };
+
+        Ok(Self {
+            record,
+            length,
+            rental_duration,
+            language_id,
+            original_language_id,
+        })
+    }
+
+    fn values(&self) -> [&(dyn ToSql + Sync); COLUMNS.len()] {
+        [
+            &self.record.title,
+            &self.record.description,
+            &self.length,
+            &self.record.release_year,
+            &self.language_id,
+            &self.original_language_id,
+            &self.rental_duration,
+            &self.record.rental_rate,
+            &self.record.replacement_cost,
+            &self.record.rating,
+            &self.record.special_features,
+        ]
+    }
+}
+
+// postgres rejects the NUL character in text values
+fn check_text(name: &str, value: &str) -> Result<(), String> {
+    if value.contains('\0') {
+        return Err(format!("the {} must not contain NUL characters", name));
+    }
+
+    Ok(())
+}
+
+// smallint
+fn positive_smallint(name: &str, value: i64) -> Result<i16, String> {
+    i16::try_from(value)
+        .ok()
+        .filter(|value| *value >= 1)
+        .ok_or_else(|| format!("the {} {} is not between 1 and {}", name, value, i16::MAX))
+}
+
+fn check_amount(name: &str, amount: Decimal, max: Decimal) -> Result<(), String> {
+    if amount.is_sign_negative() || amount > max || amount.scale() > 2 {
+        return Err(format!(
+            "the {} {} is not between 0 and {} with at most 2 decimal places",
+            name, amount, max
+        ));
+    }
+
+    Ok(())
+}
+
+fn language(languages: &FxHashMap<String, i32>, name: &str) -> Result<i32, String> {
+    languages
+        .get(&name.trim().to_lowercase())
+        .copied()
+        .ok_or_else(|| format!("the language '{}' does not exist", name))
+}
+
+// The records of the file together with the line, on which each of them
+// starts. Reading the file fails the iteration, a malformed record does not.
+enum Records<R> {
+    Csv {
+        header: Vec<String>,
+        rows: CsvRows<R>,
+    },
+    Ndjson {
+        lines: std::iter::Enumerate<std::io::Lines<R>>,
+    },
+}
+
+impl<R: BufRead> Records<R> {
+    fn new(reader: R, format: ImportFormat) -> std::io::Result<Self> {
+        match format {
+            ImportFormat::Ndjson => Ok(Records::Ndjson {
+                lines: reader.lines().enumerate(),
+            }),
+            ImportFormat::Csv => {
+                let mut rows = CsvRows::new(reader);
+                let header = match rows.next().transpose()? {
+                    Some((_, Ok(header))) => header,
+                    Some((_, Err(e))) => return Err(invalid_data(e)),
+                    None => vec![],
+                };
+
+                for column in &header {
+                    if !CSV_COLUMNS.contains(&column.as_str()) {
+                        return Err(invalid_data(format!(
+                            "unknown column '{}', the known columns are: {}",
+                            column,
+                            CSV_COLUMNS.join(", ")
+                        )));
+                    }
+                }
+
+                Ok(Records::Csv { header, rows })
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = std::io::Result<(usize, Result<FilmRecord, String>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Records::Ndjson { lines } => loop {
+                let (idx, line) = lines.next()?;
+                let line = match line {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => line,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                let record = serde_json::from_str(&line).map_err(|e| e.to_string());
+                return Some(Ok((idx + 1, record)));
+            },
+
+            Records::Csv { header, rows } => loop {
+                let (line, fields) = match rows.next()? {
+                    Ok(row) => row,
+                    Err(e) => return Some(Err(e)),
+                };
+                let fields = match fields {
+                    Ok(fields) if fields.len() == 1 && fields[0].is_empty() => continue,
+                    Ok(fields) => fields,
+                    Err(e) => return Some(Ok((line, Err(e)))),
+                };
+
+                return Some(Ok((line, csv_record(header, &fields))));
+            },
+        }
+    }
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+// The CSV columns are named after the fields of `FilmRecord`
+const CSV_COLUMNS: [&str; 11] = [
+    "title",
+    "description",
+    "length",
+    "release_year",
+    "language",
+    "original_language",
+    "rental_duration",
+    "rental_rate",
+    "replacement_cost",
+    "rating",
+    "special_features",
+];
+
+fn csv_record(header: &[String], fields: &[String]) -> Result<FilmRecord, String> {
+    if fields.len() != header.len() {
+        return Err(format!(
+            "expected {} columns, but found {}",
+            header.len(),
+            fields.len()
+        ));
+    }
+
+    // the empty cells are treated as missing values
+    let cells = header
+        .iter()
+        .map(String::as_str)
+        .zip(fields.iter().map(String::as_str))
+        .filter(|(_, value)| !value.is_empty())
+        .collect::<FxHashMap<_, _>>();
+
+    Ok(FilmRecord {
+        title: required(&cells, "title")?,
+        description: required(&cells, "description")?,
+        length: required(&cells, "length")?,
+        release_year: optional(&cells, "release_year")?,
+        language: required(&cells, "language")?,
+        original_language: optional(&cells, "original_language")?,
+        rental_duration: required(&cells, "rental_duration")?,
+        rental_rate: required(&cells, "rental_rate")?,
+        replacement_cost: required(&cells, "replacement_cost")?,
+        rating: optional(&cells, "rating")?,
+        // e.g. "Trailers,Deleted Scenes"
+        special_features: cells.get("special_features").map(|value| {
+            value
+                .split(',')
+                .map(|feature| feature.trim().to_owned())
+                .collect()
+        }),
+    })
+}
+
+fn required<T: DeserializeOwned>(cells: &FxHashMap<&str, &str>, column: &str) -> Result<T, String> {
+    optional(cells, column)?.ok_or_else(|| format!("the column '{}' is required", column))
+}
+
+// The cells are untyped, so they are read as JSON strings (e.g. titles,
+// ratings, decimals) and otherwise as JSON literals (e.g. numbers)
+fn optional<T: DeserializeOwned>(
+    cells: &FxHashMap<&str, &str>,
+    column: &str,
+) -> Result<Option<T>, String> {
+    let Some(value) = cells.get(column) else {
+        return Ok(None);
+    };
+
+    serde_json::from_value(Value::String(value.to_string()))
+        .or_else(|_| serde_json::from_str(value))
+        .map(Some)
+        .map_err(|_| format!("the column '{}' has an invalid value '{}'", column, value))
+}
+
+// Splits RFC 4180 CSV into rows, reading the file line by line. Quoted fields
+// may contain commas, line breaks and quotes, which are escaped by doubling them.
+struct CsvRows<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> CsvRows<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, line: 0 }
+    }
+
+    // The next line including its line break, `None` at the end of the file
+    fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut text = String::new();
+        if self.reader.read_line(&mut text)? == 0 {
+            return Ok(None);
+        }
+
+        self.line += 1;
+        Ok(Some(text))
+    }
+}
+
+impl<R: BufRead> Iterator for CsvRows<R> {
+    type Item = std::io::Result<(usize, Result<Vec<String>, String>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = match self.read_line() {
+            Ok(text) => text?,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let start = self.line;
+        let mut fields = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        // only a separator or the line break may follow the closing quote
+        let mut closed = false;
+
+        loop {
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted => match chars.peek() {
+                        Some('"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        _ => {
+                            quoted = false;
+                            closed = true;
+                        }
+                    },
+
+                    '\n' if !quoted => {}
+                    '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                    ',' if !quoted => {
+                        fields.push(std::mem::take(&mut field));
+                        closed = false;
+                    }
+                    c if closed => {
+                        let message = format!(
+                            "unexpected '{}' after the closing quote of column {}",
+                            c,
+                            fields.len() + 1
+                        );
+                        return Some(Ok((start, Err(message))));
+                    }
+                    '"' if field.is_empty() => quoted = true,
+                    c => field.push(c),
+                }
+            }
+
+            if !quoted {
+                break;
+            }
+
+            // the quoted field continues on the next line
+            text = match self.read_line() {
+                Ok(Some(text)) => text,
+                Ok(None) => {
+                    return Some(Ok((start, Err("unterminated quoted field".to_owned()))));
+                }
+                Err(e) => return Some(Err(e)),
+            };
+        }
+
+        fields.push(field);
+        Some(Ok((start, Ok(fields))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_rows(content: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+        CsvRows::new(content.as_bytes())
+            .collect::<std::io::Result<_>>()
+            .unwrap()
+    }
+
+    fn records(content: &str, format: ImportFormat) -> Vec<(usize, Result<FilmRecord, String>)> {
+        Records::new(content.as_bytes(), format)
+            .unwrap()
+            .collect::<std::io::Result<_>>()
+            .unwrap()
+    }
+
+    fn fields(values: &[&str]) -> Result<Vec<String>, String> {
+        Ok(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    const HEADER: &str =
+        "title,description,length,language,rental_duration,rental_rate,replacement_cost\n";
+
+    #[test]
+    fn quoted_fields_contain_commas_and_escaped_quotes() {
+        let rows = csv_rows("a,\"b, c\",\"say \"\"hi\"\"\"\r\nd,\"\",e\"f\n");
+
+        assert_eq!(
+            rows,
+            [
+                (1, fields(&["a", "b, c", "say \"hi\""])),
+                (2, fields(&["d", "", "e\"f"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_after_the_closing_quote_is_rejected() {
+        let rows = csv_rows("\"a\"b,c\n\"\"\"x\",\"\" \"\"\nd,\"e\"\n");
+
+        assert_eq!(
+            rows,
+            [
+                (
+                    1,
+                    Err("unexpected 'b' after the closing quote of column 1".to_owned())
+                ),
+                (
+                    2,
+                    Err("unexpected ' ' after the closing quote of column 2".to_owned())
+                ),
+                (3, fields(&["d", "e"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_line_fields_report_the_line_they_start_on() {
+        let rows = csv_rows("a,\"b\nc\r\nd\"\ne,f\n\"g");
+
+        assert_eq!(
+            rows,
+            [
+                (1, fields(&["a", "b\nc\r\nd"])),
+                (4, fields(&["e", "f"])),
+                (5, Err("unterminated quoted field".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn rows_with_missing_or_extra_columns_are_rejected() {
+        let content = format!(
+            "{}A,b,90,English,3,4.99,19.99\nB,b,90\n\nC,b,90,English,3,4.99,19.99,x\n",
+            HEADER
+        );
+        let records = records(&content, ImportFormat::Csv);
+
+        let lines = records.iter().map(|(line, _)| *line).collect::<Vec<_>>();
+        assert_eq!(lines, [2, 3, 5]);
+        assert_eq!(records[0].1.as_ref().unwrap().title, "A");
+        assert_eq!(
+            records[1].1.as_ref().unwrap_err(),
+            "expected 7 columns, but found 3"
+        );
+        assert_eq!(
+            records[2].1.as_ref().unwrap_err(),
+            "expected 7 columns, but found 8"
+        );
+    }
+
+    #[test]
+    fn unknown_csv_columns_fail_the_import() {
+        let error = Records::new("title,nope\n".as_bytes(), ImportFormat::Csv)
+            .err()
+            .unwrap();
+
+        assert!(error.to_string().contains("'nope'"), "{}", error);
+    }
+
+    #[test]
+    fn malformed_ndjson_lines_are_rejected_one_by_one() {
+        let valid = r#"{"title":"A","description":"b","length":90,"language":"English","rental_duration":3,"rental_rate":"4.99","replacement_cost":"19.99"}"#;
+        let content = format!("{}\n{{bad\n\n{}\n", valid, valid);
+        let records = records(&content, ImportFormat::Ndjson);
+
+        let lines = records.iter().map(|(line, _)| *line).collect::<Vec<_>>();
+        assert_eq!(lines, [1, 2, 4]);
+        assert!(records[0].1.is_ok());
+        assert!(records[1].1.is_err());
+        assert!(records[2].1.is_ok());
+    }
+
+    fn film_row(change: impl FnOnce(&mut FilmRecord)) -> Result<FilmRow, String> {
+        let mut record = FilmRecord {
+            title: "A".to_owned(),
+            description: "b".to_owned(),
+            length: 90,
+            release_year: Some(2006),
+            language: "English".to_owned(),
+            original_language: None,
+            rental_duration: 3,
+            rental_rate: Decimal::new(499, 2),
+            replacement_cost: Decimal::new(1999, 2),
+            rating: None,
+            special_features: None,
+        };
+        change(&mut record);
+
+        let languages = FxHashMap::from_iter([("english".to_owned(), 1)]);
+        FilmRow::new(record, &languages)
+    }
+
+    #[test]
+    fn rows_are_checked_against_the_column_types() {
+        assert!(film_row(|_| {}).is_ok());
+        assert!(film_row(|r| r.title = "T".repeat(255)).is_ok());
+
+        let rejected: [fn(&mut FilmRecord); 8] = [
+            |r| r.title = "T".repeat(256),
+            |r| r.description = "a\0b".to_owned(),
+            |r| r.length = 32768,
+            |r| r.rental_duration = 0,
+            |r| r.release_year = Some(1900),
+            |r| r.rental_rate = Decimal::new(10000, 2),
+            |r| r.replacement_cost = Decimal::new(-1, 2),
+            |r| r.language = "Klingon".to_owned(),
+        ];
+        for (case, change) in rejected.into_iter().enumerate() {
+            assert!(film_row(change).is_err(), "case {}", case);
+        }
+    }
+}
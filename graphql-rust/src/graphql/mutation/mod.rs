@@ -0,0 +1,19 @@
+use async_graphql::MergedObject;
+
+mod actor;
+mod film;
+mod film_import;
+mod rental;
+
+pub use actor::ActorMutation;
+pub use film::FilmMutation;
+pub use film_import::FilmImportMutation;
+pub use rental::RentalMutation;
+
+#[derive(Default, MergedObject)]
+pub struct Mutation(
+    ActorMutation,
+    FilmMutation,
+    FilmImportMutation,
+    RentalMutation,
+);
@@ -0,0 +1,405 @@
+use crate::graphql::core::filter::InputFilter;
+use crate::graphql::core::loader::FromRow;
+use crate::graphql::core::mutation::{lock_version, missing_ids, Assignments, MutationError};
+use crate::graphql::model::{
+    Customer, CustomerFilter, Film, FilmFilter, Inventory, Payment, PaymentFilter, Rental,
+    RentalFilter, Staff,
+};
+use async_graphql::{Context, ErrorExtensions, InputObject, Object};
+use deadpool_postgres::{Pool, Transaction};
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+use tokio_postgres::error::SqlState;
+
+#[derive(Debug, Clone, InputObject)]
+pub struct ReturnPaymentInput {
+    // must be positive
+    pub amount: Decimal,
+    // defaults to the staff member, who handed out the rental
+    pub staff_id: Option<i32>,
+    // defaults to the return date. The payment table is partitioned by the
+    // payment date, the date must be covered by one of its partitions.
+    pub payment_date: Option<OffsetDateTime>,
+}
+
+#[derive(Default)]
+pub struct RentalMutation;
+
+#[Object]
+impl RentalMutation {
+    // Rents out the first copy of the film, which is in stock at the store
+    async fn rent_film<'a>(
+        &self,
+        ctx: &Context<'a>,
+        film_id: i32,
+        store_id: i32,
+        customer_id: i32,
+        staff_id: i32,
+    ) -> async_graphql::Result<Rental> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+
+        check_customer(&tx, customer_id).await?;
+        check_staff(&tx, staff_id, store_id).await?;
+        if !missing_ids::<Film>(&tx, FilmFilter::TABLE_NAME, &[film_id])
+            .await?
+            .is_empty()
+        {
+            return Err(MutationError::NotFound {
+                entity: "film",
+                id: film_id,
+            }
+            .extend());
+        }
+
+        let Some(inventory_id) = copy_in_stock(&tx, film_id, store_id).await? else {
+            return Err(MutationError::NoCopyInStock { film_id, store_id }.extend());
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let mut assignments = Assignments::default();
+        assignments.set(Rental::COLUMN_RENTAL_DATE, &now);
+        assignments.set(Rental::COLUMN_INVENTORY_ID, &inventory_id);
+        assignments.set(Rental::COLUMN_CUSTOMER_ID, &customer_id);
+        assignments.set(Rental::COLUMN_STAFF_ID, &staff_id);
+
+        let (sql, params) = assignments.insert::<Rental>(RentalFilter::TABLE_NAME);
+        tracing::debug!(query=sql, paramters=?params, table=RentalFilter::TABLE_NAME);
+
+        let stm = tx.prepare_cached(&sql).await?;
+        let row = tx.query_one(&stm, &params).await?;
+        let rental = Rental::from_row(&row)?;
+
+        tx.commit().await?;
+        Ok(rental)
+    }
+
+    // Marks the rental as returned, optionally recording the payment for it
+    async fn return_rental<'a>(
+        &self,
+        ctx: &Context<'a>,
+        rental_id: i32,
+        payment: Option<ReturnPaymentInput>,
+        expected_last_update: Option<OffsetDateTime>,
+    ) -> async_graphql::Result<Rental> {
+        let db = ctx.data_unchecked::<Pool>();
+        let mut con = db.get().await?;
+        let tx = con.transaction().await?;
+        let returned = mark_returned(&tx, rental_id, payment, expected_last_update).await?;
+
+        tx.commit().await?;
+        Ok(returned)
+    }
+}
+
+async fn mark_returned(
+    tx: &Transaction<'_>,
+    rental_id: i32,
+    payment: Option<ReturnPaymentInput>,
+    expected_last_update: Option<OffsetDateTime>,
+) -> async_graphql::Result<Rental> {
+    lock_version::<Rental>(
+        tx,
+        RentalFilter::TABLE_NAME,
+        "rental",
+        rental_id,
+        expected_last_update,
+    )
+    .await?;
+
+    let columns = [
+        Rental::COLUMN_CUSTOMER_ID,
+        Rental::COLUMN_STAFF_ID,
+        Rental::COLUMN_RETURN_DATE,
+    ];
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} = $1",
+        columns.join(", "),
+        RentalFilter::TABLE_NAME,
+        Rental::COLUMN_RENTAL_ID,
+    );
+    let stm = tx.prepare_cached(&sql).await?;
+    let row = tx.query_one(&stm, &[&rental_id]).await?;
+    let rental = Rental::from_columns(&row, &columns)?;
+    if rental.return_date.is_some() {
+        return Err(MutationError::AlreadyReturned { rental_id }.extend());
+    }
+    if let Some(payment) = &payment {
+        if payment.amount <= Decimal::ZERO {
+            return Err(MutationError::InvalidAmount {
+                amount: payment.amount,
+            }
+            .extend());
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut assignments = Assignments::default();
+    assignments.set(Rental::COLUMN_RETURN_DATE, &now);
+
+    let (sql, params) = assignments.update::<Rental>(RentalFilter::TABLE_NAME, &rental_id);
+    tracing::debug!(query=sql, paramters=?params, table=RentalFilter::TABLE_NAME);
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let row = tx.query_one(&stm, &params).await?;
+    let returned = Rental::from_row(&row)?;
+
+    if let Some(payment) = payment {
+        let staff_id = payment.staff_id.unwrap_or(rental.staff_id);
+        if !missing_ids::<Staff>(tx, Staff::TABLE_NAME, &[staff_id])
+            .await?
+            .is_empty()
+        {
+            return Err(MutationError::NotFound {
+                entity: "staff member",
+                id: staff_id,
+            }
+            .extend());
+        }
+
+        let payment = NewPayment {
+            customer_id: rental.customer_id,
+            staff_id,
+            rental_id,
+            amount: payment.amount,
+            payment_date: payment.payment_date.unwrap_or(now),
+        };
+        insert_payment(tx, &payment).await?;
+    }
+
+    Ok(returned)
+}
+
+struct NewPayment {
+    customer_id: i32,
+    staff_id: i32,
+    rental_id: i32,
+    amount: Decimal,
+    payment_date: OffsetDateTime,
+}
+
+// The payment table is partitioned by the payment date and has no default
+// partition, so a date outside of the partitions is rejected by postgres
+async fn insert_payment(tx: &Transaction<'_>, payment: &NewPayment) -> async_graphql::Result<()> {
+    let mut assignments = Assignments::default();
+    assignments.set(Payment::COLUMN_CUSTOMER_ID, &payment.customer_id);
+    assignments.set(Payment::COLUMN_STAFF_ID, &payment.staff_id);
+    assignments.set(Payment::COLUMN_RENTAL_ID, &payment.rental_id);
+    assignments.set(Payment::COLUMN_AMOUNT, &payment.amount);
+    assignments.set(Payment::COLUMN_PAYMENT_DATE, &payment.payment_date);
+
+    let (sql, params) = assignments.insert::<Payment>(PaymentFilter::TABLE_NAME);
+    tracing::debug!(query=sql, paramters=?params, table=PaymentFilter::TABLE_NAME);
+
+    let stm = tx.prepare_cached(&sql).await?;
+    match tx.execute(&stm, &params).await {
+        Ok(_) => Ok(()),
+        Err(e) if is_missing_partition(&e) => Err(MutationError::PaymentDateOutOfRange {
+            rental_id: payment.rental_id,
+            payment_date: payment.payment_date,
+        }
+        .extend()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// The row routing of a partitioned table fails with a check violation, which
+// is told apart from the violations of the check constraints by its message
+fn is_missing_partition(e: &tokio_postgres::Error) -> bool {
+    e.as_db_error().is_some_and(|e| {
+        *e.code() == SqlState::CHECK_VIOLATION
+            && e.message().starts_with("no partition of relation")
+    })
+}
+
+// The customer must be active and must not have overdue rentals. The customer
+// stays locked until the commit, so concurrent rentals are checked one by one.
+async fn check_customer(tx: &Transaction<'_>, customer_id: i32) -> async_graphql::Result<()> {
+    let lock = format!(
+        "SELECT {} FROM {} WHERE {} = $1 FOR UPDATE",
+        Customer::COLUMN_ACTIVE,
+        CustomerFilter::TABLE_NAME,
+        Customer::COLUMN_CUSTOMER_ID,
+    );
+    let stm = tx.prepare_cached(&lock).await?;
+    let Some(row) = tx.query_opt(&stm, &[&customer_id]).await? else {
+        return Err(MutationError::NotFound {
+            entity: "customer",
+            id: customer_id,
+        }
+        .extend());
+    };
+    if !row.try_get::<_, bool>(0)? {
+        return Err(MutationError::CustomerInactive { customer_id }.extend());
+    }
+
+    // a rental is overdue, when it is not returned within the rental duration of the film
+    let overdue = format!(
+        "SELECT count(*) FROM {rental} r JOIN {inventory} i ON i.{inventory_id} = r.{inventory_id} JOIN {film} f ON f.{film_id} = i.{film_id} WHERE r.{customer_id} = $1 AND r.{return_date} IS NULL AND r.{rental_date} + f.{rental_duration} * interval '1 day' < now()",
+        rental = RentalFilter::TABLE_NAME,
+        inventory = Inventory::TABLE_NAME,
+        film = FilmFilter::TABLE_NAME,
+        inventory_id = Inventory::COLUMN_INVENTORY_ID,
+        film_id = Film::COLUMN_FILM_ID,
+        customer_id = Rental::COLUMN_CUSTOMER_ID,
+        return_date = Rental::COLUMN_RETURN_DATE,
+        rental_date = Rental::COLUMN_RENTAL_DATE,
+        rental_duration = Film::COLUMN_RENTAL_DURATION,
+    );
+    tracing::debug!(query = overdue, table = RentalFilter::TABLE_NAME);
+    let stm = tx.prepare_cached(&overdue).await?;
+    let count: i64 = tx.query_one(&stm, &[&customer_id]).await?.try_get(0)?;
+    if count > 0 {
+        return Err(MutationError::OverdueRentals { customer_id, count }.extend());
+    }
+
+    Ok(())
+}
+
+async fn check_staff(
+    tx: &Transaction<'_>,
+    staff_id: i32,
+    store_id: i32,
+) -> async_graphql::Result<()> {
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} = $1",
+        Staff::COLUMN_STORE_ID,
+        Staff::TABLE_NAME,
+        Staff::COLUMN_STAFF_ID,
+    );
+    let stm = tx.prepare_cached(&sql).await?;
+    let Some(row) = tx.query_opt(&stm, &[&staff_id]).await? else {
+        return Err(MutationError::NotFound {
+            entity: "staff member",
+            id: staff_id,
+        }
+        .extend());
+    };
+    if row.try_get::<_, i32>(0)? != store_id {
+        return Err(MutationError::StaffNotInStore { staff_id, store_id }.extend());
+    }
+
+    Ok(())
+}
+
+// Same as Sakila's `inventory_in_stock` - a copy is in stock, when none of its
+// rentals is still outstanding. The copies, which are being rented out by a
+// concurrent transaction, are skipped.
+async fn copy_in_stock(
+    tx: &Transaction<'_>,
+    film_id: i32,
+    store_id: i32,
+) -> async_graphql::Result<Option<i32>> {
+    let sql = format!(
+        "SELECT i.{inventory_id} FROM {inventory} i WHERE i.{film_id} = $1 AND i.{store_id} = $2 AND NOT EXISTS (SELECT 1 FROM {rental} r WHERE r.{inventory_id} = i.{inventory_id} AND r.{return_date} IS NULL) ORDER BY i.{inventory_id} LIMIT 1 FOR UPDATE SKIP LOCKED",
+        inventory = Inventory::TABLE_NAME,
+        rental = RentalFilter::TABLE_NAME,
+        inventory_id = Inventory::COLUMN_INVENTORY_ID,
+        film_id = Inventory::COLUMN_FILM_ID,
+        store_id = Inventory::COLUMN_STORE_ID,
+        return_date = Rental::COLUMN_RETURN_DATE,
+    );
+    tracing::debug!(query = sql, table = Inventory::TABLE_NAME);
+
+    let stm = tx.prepare_cached(&sql).await?;
+    let row = tx.query_opt(&stm, &[&film_id, &store_id]).await?;
+    Ok(match row {
+        Some(row) => Some(row.try_get(0)?),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::config::{ServiceConfig, SPLIT_AT_DOUBLE_UNDERSCORE};
+    use crate::init::init_db_pool;
+    use time::macros::datetime;
+
+    // The tests need the sakila database, configured like the service, e.g.
+    // `CFG__DB__DB_NAME=sakila cargo test -- --ignored`. They run in a
+    // transaction, which is rolled back when it is dropped.
+    async fn connection() -> deadpool_postgres::Object {
+        let cfg: ServiceConfig = config::load("CFG__", SPLIT_AT_DOUBLE_UNDERSCORE).unwrap();
+        let db = init_db_pool(&cfg.db).unwrap();
+        db.get().await.unwrap()
+    }
+
+    // Marks the first rental as not returned yet and returns it
+    async fn return_rental(
+        tx: &Transaction<'_>,
+        payment: Option<ReturnPaymentInput>,
+    ) -> async_graphql::Result<Rental> {
+        tx.execute(
+            "UPDATE rental SET return_date = NULL WHERE rental_id = 1",
+            &[],
+        )
+        .await
+        .unwrap();
+        mark_returned(tx, 1, payment, None).await
+    }
+
+    fn payment(amount: Decimal, payment_date: OffsetDateTime) -> Option<ReturnPaymentInput> {
+        Some(ReturnPaymentInput {
+            amount,
+            staff_id: None,
+            payment_date: Some(payment_date),
+        })
+    }
+
+    fn code(error: async_graphql::Error) -> Option<async_graphql::Value> {
+        error.extensions.and_then(|e| e.get("code").cloned())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn rental_is_returned_with_its_payment() {
+        let mut con = connection().await;
+        let tx = con.transaction().await.unwrap();
+        let count = "SELECT count(*) FROM payment WHERE rental_id = 1";
+        let before: i64 = tx.query_one(count, &[]).await.unwrap().get(0);
+
+        let payment = payment(Decimal::new(299, 2), datetime!(2022-02-15 10:00 UTC));
+        let returned = return_rental(&tx, payment).await.unwrap();
+
+        assert_eq!(returned.rental_id, 1);
+        assert!(returned.return_date.is_some());
+        let after: i64 = tx.query_one(count, &[]).await.unwrap().get(0);
+        assert_eq!(after, before + 1);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn rental_is_returned_only_once() {
+        let mut con = connection().await;
+        let tx = con.transaction().await.unwrap();
+        return_rental(&tx, None).await.unwrap();
+
+        let error = mark_returned(&tx, 1, None, None).await.unwrap_err();
+        assert_eq!(code(error), Some("ALREADY_RETURNED".into()));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn payment_amount_must_be_positive() {
+        let mut con = connection().await;
+        let tx = con.transaction().await.unwrap();
+
+        let payment = payment(Decimal::ZERO, datetime!(2022-02-15 10:00 UTC));
+        let error = return_rental(&tx, payment).await.unwrap_err();
+        assert_eq!(code(error), Some("INVALID_AMOUNT".into()));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn payment_outside_of_the_partitions_is_out_of_range() {
+        let mut con = connection().await;
+        let tx = con.transaction().await.unwrap();
+
+        let payment = payment(Decimal::new(299, 2), datetime!(2100-01-01 0:00 UTC));
+        let error = return_rental(&tx, payment).await.unwrap_err();
+        assert_eq!(code(error), Some("PAYMENT_DATE_OUT_OF_RANGE".into()));
+    }
+}
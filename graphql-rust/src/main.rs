@@ -15,7 +15,7 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 
     let db = init_db_pool(&cfg.db)?;
     let state = AppState::new(db.clone());
-    let schema = build_schema(state.clone());
+    let schema = build_schema(state.clone(), cfg.query.clone());
 
     start_server(cfg, state, schema).await?;
 
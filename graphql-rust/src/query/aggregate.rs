@@ -0,0 +1,69 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AggregateFunction {
+    Avg,
+    Min,
+    Max,
+    Sum,
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+            AggregateFunction::Sum => "sum",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GroupKey<'f> {
+    // A column of the queried table
+    Column(&'f str),
+
+    // A column of a join table that references the queried table. Rows
+    // referenced by several join table rows are counted in each group.
+    JoinTable {
+        join_table: &'f str,
+        parent_table_join_col: &'f str,
+        join_table_parent_col: &'f str,
+        column: &'f str,
+    },
+}
+
+impl<'f> GroupKey<'f> {
+    // The name of the column, under which the key of the group is returned
+    pub fn alias(&self) -> &'f str {
+        match self {
+            GroupKey::Column(column) => column,
+            GroupKey::JoinTable { column, .. } => column,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregateColumn<'f> {
+    pub function: AggregateFunction,
+    pub column: &'f str,
+}
+
+impl AggregateColumn<'_> {
+    // The name of the column, under which the aggregated value is returned
+    pub fn alias(&self) -> String {
+        format!("{}_{}", self.function, self.column)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Aggregate<'f> {
+    pub group_by: Vec<GroupKey<'f>>,
+    pub columns: Vec<AggregateColumn<'f>>,
+}
+
+// The name of the column, under which the number of rows in a group is returned
+pub const AGGREGATE_COUNT_COLUMN: &str = "count";
@@ -36,8 +36,14 @@ impl<'f> FilterGroup<'f> {
             return;
         }
 
+        // a negated group cannot be merged with anything else
+        if group.combinator == Combinator::Not {
+            self.groups.push(group);
+            return;
+        }
+
         // groups of the same type can be compressed into one group
-        if self.combinator == group.combinator {
+        if self.combinator.joined_by() == group.combinator {
             self.filters.extend(group.filters);
             for g in group.groups {
                 self.add_group(g);
@@ -1,4 +1,5 @@
 use crate::query::join_column_filter::JoinColumnFilter;
+use crate::query::join_table_count_filter::JoinTableCountFilter;
 use crate::query::join_table_filter::JoinTableFilter;
 use crate::query::value_filter::ValueFilter;
 use crate::query::visitor::Visitor;
@@ -8,6 +9,7 @@ pub enum FilterType<'f> {
     ValueFilter(ValueFilter<'f>),
     JoinColumnFilter(JoinColumnFilter<'f>),
     JoinTableFilter(JoinTableFilter<'f>),
+    JoinTableCountFilter(JoinTableCountFilter<'f>),
 }
 
 impl<'f> FilterType<'f> {
@@ -33,3 +35,9 @@ impl<'f> From<JoinTableFilter<'f>> for FilterType<'f> {
         FilterType::JoinTableFilter(value)
     }
 }
+
+impl<'f> From<JoinTableCountFilter<'f>> for FilterType<'f> {
+    fn from(value: JoinTableCountFilter<'f>) -> Self {
+        FilterType::JoinTableCountFilter(value)
+    }
+}
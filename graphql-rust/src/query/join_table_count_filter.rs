@@ -0,0 +1,43 @@
+use crate::query::join_table_filter::JoinTableFilter;
+use crate::query::ops::Operation;
+use crate::query::visitor::Visitor;
+use tokio_postgres::types::ToSql;
+
+// Compares the number of rows in a join table that reference the parent row
+// (and optionally match the child table filter) with a value
+#[derive(Debug, Clone)]
+pub struct JoinTableCountFilter<'f> {
+    join: JoinTableFilter<'f>,
+    operation: Operation,
+    value: &'f (dyn ToSql + Sync),
+}
+
+impl<'f> JoinTableCountFilter<'f> {
+    pub fn new(
+        join: JoinTableFilter<'f>,
+        operation: Operation,
+        value: &'f (dyn ToSql + Sync),
+    ) -> Self {
+        Self {
+            join,
+            operation,
+            value,
+        }
+    }
+
+    pub fn accept(&self, v: &mut dyn Visitor<'f>) {
+        v.on_join_table_count_filter(self);
+    }
+
+    pub fn join(&self) -> &JoinTableFilter<'f> {
+        &self.join
+    }
+
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    pub fn value(&self) -> &'f (dyn ToSql + Sync) {
+        self.value
+    }
+}
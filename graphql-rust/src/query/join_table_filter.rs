@@ -1,3 +1,4 @@
+use crate::query::ops::Quantifier;
 use crate::query::visitor::Visitor;
 use crate::query::TableFilter;
 
@@ -11,6 +12,8 @@ pub struct JoinTableFilter<'f> {
 
     child_table_join_col: &'f str,
     join_table_child_col: &'f str,
+
+    quantifier: Quantifier,
 }
 
 impl<'f> JoinTableFilter<'f> {
@@ -29,9 +32,15 @@ impl<'f> JoinTableFilter<'f> {
             join_table_parent_col,
             child_table_join_col,
             join_table_child_col,
+            quantifier: Quantifier::Some,
         }
     }
 
+    pub fn quantified(mut self, quantifier: Quantifier) -> Self {
+        self.quantifier = quantifier;
+        self
+    }
+
     pub fn accept(&self, v: &mut dyn Visitor<'f>) {
         v.on_join_table_filter(self);
     }
@@ -59,4 +68,8 @@ impl<'f> JoinTableFilter<'f> {
     pub fn join_table_child_col(&self) -> &'f str {
         self.join_table_child_col
     }
+
+    pub fn quantifier(&self) -> Quantifier {
+        self.quantifier
+    }
 }
@@ -1,19 +1,33 @@
+mod aggregate;
 mod filter_group;
 mod filter_type;
 mod join_column_filter;
+mod join_table_count_filter;
 mod join_table_filter;
 mod joined_table;
 mod ops;
+mod order;
+mod page;
+mod projection;
 mod sql_builder;
 mod table_filter;
+mod text_search;
 mod value_filter;
 mod visitor;
 
+pub use aggregate::{
+    Aggregate, AggregateColumn, AggregateFunction, GroupKey, AGGREGATE_COUNT_COLUMN,
+};
 pub use filter_group::FilterGroup;
 pub use join_column_filter::JoinColumnFilter;
+pub use join_table_count_filter::JoinTableCountFilter;
 pub use join_table_filter::JoinTableFilter;
 pub use joined_table::JoinedTable;
-pub use ops::{Combinator, Operation};
+pub use ops::{Combinator, Operation, Quantifier};
+pub use order::{Direction, Nulls, OrderBy, OrderKey};
+pub use page::Page;
+pub use projection::{ProjectedRelation, Projection, RelationJoin, PREFETCHED_KEY};
 pub use sql_builder::SqlVisitor;
 pub use table_filter::TableFilter;
+pub use text_search::{TextSearch, RANK_COLUMN};
 pub use value_filter::ValueFilter;
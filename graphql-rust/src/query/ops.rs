@@ -4,6 +4,18 @@ use std::fmt::{Display, Formatter};
 pub enum Combinator {
     And,
     Or,
+    // A conjunction of all filters in the group, which is then negated
+    Not,
+}
+
+impl Combinator {
+    // The logical operator used to join the members of a group
+    pub fn joined_by(self) -> Combinator {
+        match self {
+            Combinator::Not => Combinator::And,
+            c => c,
+        }
+    }
 }
 
 impl Display for Combinator {
@@ -11,12 +23,22 @@ impl Display for Combinator {
         let s = match self {
             Combinator::And => "AND",
             Combinator::Or => "OR",
+            Combinator::Not => "NOT",
         };
 
         f.write_str(s)
     }
 }
 
+// How many of the related rows must match a filter on a to-many relationship
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Quantifier {
+    #[default]
+    Some,
+    Every,
+    None,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Operation {
     Eq,
@@ -30,4 +52,11 @@ pub enum Operation {
     IsNull,
     IsNotNull,
     Contains,
+    IContains,
+    StartsWith,
+    EndsWith,
+    ILike,
+    Regex,
+    ArrayContains,
+    TextSearch,
 }
@@ -0,0 +1,66 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Direction::Asc => "ASC",
+            Direction::Desc => "DESC",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+impl Display for Nulls {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Nulls::First => "NULLS FIRST",
+            Nulls::Last => "NULLS LAST",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OrderKey<'f> {
+    // A column of the queried table
+    Column(&'f str),
+
+    // A column of a table referenced by a foreign key of the queried table
+    JoinColumn {
+        table: &'f str,
+        parent_column: &'f str,
+        child_column: &'f str,
+        column: &'f str,
+    },
+
+    // The number of rows in a join table that reference the queried table
+    JoinTableCount {
+        join_table: &'f str,
+        parent_table_join_col: &'f str,
+        join_table_parent_col: &'f str,
+    },
+
+    // The rank of the row, according to the text search of the query
+    Rank,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderBy<'f> {
+    pub key: OrderKey<'f>,
+    pub direction: Direction,
+    pub nulls: Option<Nulls>,
+}
@@ -0,0 +1,9 @@
+use tokio_postgres::types::ToSql;
+
+pub const ROW_NUMBER_COLUMN: &str = "__row_number";
+
+#[derive(Debug, Clone)]
+pub struct Page<'f> {
+    pub limit: &'f (dyn ToSql + Sync),
+    pub offset: Option<&'f (dyn ToSql + Sync)>,
+}
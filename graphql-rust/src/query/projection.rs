@@ -0,0 +1,42 @@
+use tokio_postgres::types::ToSql;
+
+// The key of the JSON object holding the related entities, keyed by the name of
+// the field that requested them
+pub const PREFETCHED_KEY: &str = "__prefetched";
+
+// The columns and the related entities of a table, which are loaded as a
+// single JSON object per row
+#[derive(Debug, Default)]
+pub struct Projection<'p> {
+    pub columns: Vec<&'p str>,
+    pub relations: Vec<ProjectedRelation<'p>>,
+}
+
+#[derive(Debug)]
+pub struct ProjectedRelation<'p> {
+    // must be a valid GraphQL name, as it is embedded as a literal in the query
+    pub key: &'p str,
+    pub table: &'p str,
+    pub join: RelationJoin<'p>,
+    pub projection: Projection<'p>,
+}
+
+#[derive(Debug)]
+pub enum RelationJoin<'p> {
+    // A single row, referenced by a column of the parent table
+    ToOne {
+        parent_column: &'p str,
+        child_column: &'p str,
+    },
+
+    // A JSON array of the rows, referenced through a join table
+    ToMany {
+        parent_column: &'p str,
+        join_table: &'p str,
+        join_table_parent_col: &'p str,
+        join_table_child_col: &'p str,
+        child_column: &'p str,
+        primary_key: &'p str,
+        limit: &'p (dyn ToSql + Sync),
+    },
+}
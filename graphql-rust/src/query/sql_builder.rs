@@ -1,10 +1,16 @@
+use crate::query::aggregate::{Aggregate, GroupKey, AGGREGATE_COUNT_COLUMN};
 use crate::query::filter_group::FilterGroup;
 use crate::query::filter_type::FilterType;
 use crate::query::join_column_filter::JoinColumnFilter;
+use crate::query::join_table_count_filter::JoinTableCountFilter;
 use crate::query::join_table_filter::JoinTableFilter;
 use crate::query::joined_table::JoinedTable;
-use crate::query::ops::Operation;
+use crate::query::ops::{Combinator, Operation, Quantifier};
+use crate::query::order::{OrderBy, OrderKey};
+use crate::query::page::{Page, ROW_NUMBER_COLUMN};
+use crate::query::projection::{Projection, RelationJoin, PREFETCHED_KEY};
 use crate::query::table_filter::TableFilter;
+use crate::query::text_search::{TextSearch, RANK_COLUMN};
 use crate::query::value_filter::ValueFilter;
 use crate::query::visitor::Visitor;
 use std::fmt::Write;
@@ -15,6 +21,10 @@ const SQL_QUERY_BUFFER_INITIAL_CAPACITY: usize = 1024;
 #[derive(Debug, Default)]
 pub struct SqlVisitor<'v> {
     joined_table: Option<JoinedTable<'v>>,
+    page: Option<Page<'v>>,
+    order: Vec<OrderBy<'v>>,
+    rank: Option<TextSearch<'v>>,
+    loader_key: Option<&'v str>,
     next_table: u32,
     current_table: Vec<u32>,
     query: String,
@@ -33,6 +43,28 @@ impl<'v> SqlVisitor<'v> {
         }
     }
 
+    pub fn paginate(mut self, page: Page<'v>) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    pub fn order_by(mut self, order: Vec<OrderBy<'v>>) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn rank(mut self, search: TextSearch<'v>) -> Self {
+        self.rank = Some(search);
+        self
+    }
+
+    // Loads the rows of several parents at once: the value of the joined table
+    // is a list of parent keys, and each row carries its parent key under `alias`
+    pub fn loader_key(mut self, alias: &'v str) -> Self {
+        self.loader_key = Some(alias);
+        self
+    }
+
     pub fn translate(
         mut self,
         filter: &TableFilter<'v>,
@@ -40,8 +72,19 @@ impl<'v> SqlVisitor<'v> {
     ) -> (String, Vec<&'v (dyn ToSql + Sync)>) {
         self.query.reserve(SQL_QUERY_BUFFER_INITIAL_CAPACITY);
 
+        // the rows of several parents are paged per parent, so the page is cut
+        // by numbering the rows of every parent separately
+        let partition = match (self.loader_key, self.joined_table.as_ref(), &self.page) {
+            (Some(_), Some(j), Some(_)) => Some(j.join_table_filter_col),
+            _ => None,
+        };
+
         let current_table_id = self.push_new_table();
 
+        if partition.is_some() {
+            self.query.push_str("SELECT * FROM (");
+        }
+
         self.query.push_str("SELECT ");
         if select.is_empty() {
             write!(&mut self.query, "T{}.*", current_table_id).unwrap();
@@ -54,6 +97,314 @@ impl<'v> SqlVisitor<'v> {
             }
         }
 
+        if let (Some(alias), Some(j)) = (self.loader_key, self.joined_table.as_ref()) {
+            write!(
+                &mut self.query,
+                ", J.{} AS {}",
+                j.join_table_filter_col, alias
+            )
+            .unwrap();
+        }
+
+        if self.rank.is_some() {
+            self.query.push_str(", ");
+            self.write_rank();
+            write!(&mut self.query, " AS {}", RANK_COLUMN).unwrap();
+        }
+
+        if let Some(column) = partition {
+            write!(
+                &mut self.query,
+                ", ROW_NUMBER() OVER (PARTITION BY J.{}",
+                column
+            )
+            .unwrap();
+            self.write_order_by();
+            write!(&mut self.query, ") AS {}", ROW_NUMBER_COLUMN).unwrap();
+        }
+
+        self.write_from_where(filter);
+
+        match partition {
+            Some(_) => self.write_partition_page(),
+            None => {
+                self.write_order_by();
+                self.write_page();
+            }
+        }
+
+        (self.query, self.params)
+    }
+
+    // Loads each row, along with its related entities, as a single JSON object
+    // returned under the alias
+    pub fn translate_projection(
+        mut self,
+        filter: &TableFilter<'v>,
+        projection: &Projection<'v>,
+        alias: &str,
+    ) -> (String, Vec<&'v (dyn ToSql + Sync)>) {
+        self.query.reserve(SQL_QUERY_BUFFER_INITIAL_CAPACITY);
+        self.push_new_table();
+
+        self.query.push_str("SELECT ");
+        let laterals = self.write_json_object(projection, self.rank.is_some());
+        write!(&mut self.query, " AS {}", alias).unwrap();
+
+        self.write_from(filter);
+        self.write_laterals(projection, &laterals);
+        self.write_where(filter);
+        self.write_order_by();
+        self.write_page();
+
+        (self.query, self.params)
+    }
+
+    pub fn translate_count(
+        mut self,
+        filter: &TableFilter<'v>,
+        alias: &str,
+    ) -> (String, Vec<&'v (dyn ToSql + Sync)>) {
+        self.query.reserve(SQL_QUERY_BUFFER_INITIAL_CAPACITY);
+        self.push_new_table();
+
+        write!(&mut self.query, "SELECT count(*) AS {}", alias).unwrap();
+        self.write_from_where(filter);
+
+        (self.query, self.params)
+    }
+
+    pub fn translate_aggregate(
+        mut self,
+        filter: &TableFilter<'v>,
+        aggregate: &Aggregate<'v>,
+    ) -> (String, Vec<&'v (dyn ToSql + Sync)>) {
+        self.query.reserve(SQL_QUERY_BUFFER_INITIAL_CAPACITY);
+        let current_table_id = self.push_new_table();
+
+        // the group keys of the join tables are selected from their own aliases
+        let group_by = aggregate
+            .group_by
+            .iter()
+            .map(|key| match key {
+                GroupKey::Column(column) => (current_table_id, *column),
+                GroupKey::JoinTable { column, .. } => {
+                    let join_table_id = self.push_new_table();
+                    self.pop_old_table();
+                    (join_table_id, *column)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        write!(
+            &mut self.query,
+            "SELECT count(*) AS {}",
+            AGGREGATE_COUNT_COLUMN
+        )
+        .unwrap();
+        for (table_id, column) in group_by.iter() {
+            write!(&mut self.query, ", T{}.{}", table_id, column).unwrap();
+        }
+
+        // the values are always returned as numeric, regardless of the column type
+        for c in aggregate.columns.iter() {
+            write!(
+                &mut self.query,
+                ", {}(T{}.{})::numeric AS {}",
+                c.function,
+                current_table_id,
+                c.column,
+                c.alias()
+            )
+            .unwrap();
+        }
+
+        self.write_from(filter);
+        for (key, (table_id, _)) in aggregate.group_by.iter().zip(group_by.iter()) {
+            if let GroupKey::JoinTable {
+                join_table,
+                parent_table_join_col,
+                join_table_parent_col,
+                ..
+            } = key
+            {
+                write!(
+                    &mut self.query,
+                    " INNER JOIN {} AS T{} ON T{}.{} = T{}.{}",
+                    join_table,
+                    table_id,
+                    table_id,
+                    join_table_parent_col,
+                    current_table_id,
+                    parent_table_join_col
+                )
+                .unwrap();
+            }
+        }
+        self.write_where(filter);
+
+        for clause in [" GROUP BY ", " ORDER BY "] {
+            for (idx, (table_id, column)) in group_by.iter().enumerate() {
+                match idx {
+                    0 => self.query.push_str(clause),
+                    _ => self.query.push_str(", "),
+                }
+                write!(&mut self.query, "T{}.{}", table_id, column).unwrap();
+            }
+        }
+
+        (self.query, self.params)
+    }
+
+    // Writes the JSON object of the current table and returns the ids of the
+    // lateral subqueries, which the object refers to
+    fn write_json_object(&mut self, projection: &Projection<'v>, with_rank: bool) -> Vec<u32> {
+        let current_table_id = self.current_table_id();
+
+        self.query.push_str("json_build_object(");
+        for (idx, column) in projection.columns.iter().enumerate() {
+            if idx > 0 {
+                self.query.push_str(", ");
+            }
+            write!(
+                &mut self.query,
+                "'{}', T{}.{}",
+                column, current_table_id, column
+            )
+            .unwrap();
+        }
+
+        let mut laterals = Vec::with_capacity(projection.relations.len());
+        if !projection.relations.is_empty() {
+            if !projection.columns.is_empty() {
+                self.query.push_str(", ");
+            }
+
+            write!(&mut self.query, "'{}', json_build_object(", PREFETCHED_KEY).unwrap();
+            for (idx, relation) in projection.relations.iter().enumerate() {
+                let lateral_id = self.push_new_table();
+                self.pop_old_table();
+                laterals.push(lateral_id);
+
+                if idx > 0 {
+                    self.query.push_str(", ");
+                }
+                write!(&mut self.query, "'{}', L{}.value", relation.key, lateral_id).unwrap();
+            }
+            self.query.push(')');
+        }
+
+        if with_rank {
+            if !projection.columns.is_empty() || !projection.relations.is_empty() {
+                self.query.push_str(", ");
+            }
+            write!(&mut self.query, "'{}', ", RANK_COLUMN).unwrap();
+            self.write_rank();
+        }
+
+        self.query.push(')');
+        laterals
+    }
+
+    fn write_laterals(&mut self, projection: &Projection<'v>, laterals: &[u32]) {
+        let parent_table_id = self.current_table_id();
+
+        for (relation, lateral_id) in projection.relations.iter().zip(laterals.iter()) {
+            self.query.push_str(" LEFT JOIN LATERAL (");
+
+            match relation.join {
+                RelationJoin::ToOne {
+                    parent_column,
+                    child_column,
+                } => {
+                    let child_table_id = self.push_new_table();
+
+                    self.query.push_str("SELECT ");
+                    let nested = self.write_json_object(&relation.projection, false);
+                    write!(
+                        &mut self.query,
+                        " AS value FROM {} AS T{}",
+                        relation.table, child_table_id
+                    )
+                    .unwrap();
+                    self.write_laterals(&relation.projection, &nested);
+                    write!(
+                        &mut self.query,
+                        " WHERE T{}.{} = T{}.{}",
+                        child_table_id, child_column, parent_table_id, parent_column
+                    )
+                    .unwrap();
+
+                    self.pop_old_table();
+                }
+                RelationJoin::ToMany {
+                    parent_column,
+                    join_table,
+                    join_table_parent_col,
+                    join_table_child_col,
+                    child_column,
+                    primary_key,
+                    limit,
+                } => {
+                    let join_table_id = self.push_new_table();
+                    self.pop_old_table();
+                    let child_table_id = self.push_new_table();
+
+                    // the rows are limited before the aggregation, so the
+                    // ordering key is carried along with the JSON object
+                    write!(
+                        &mut self.query,
+                        "SELECT coalesce(json_agg(S{}.value ORDER BY S{}.key), '[]') AS value FROM (SELECT T{}.{} AS key, ",
+                        child_table_id, child_table_id, child_table_id, primary_key
+                    )
+                    .unwrap();
+                    let nested = self.write_json_object(&relation.projection, false);
+                    write!(
+                        &mut self.query,
+                        " AS value FROM {} AS T{} INNER JOIN {} AS T{} ON T{}.{} = T{}.{}",
+                        join_table,
+                        join_table_id,
+                        relation.table,
+                        child_table_id,
+                        join_table_id,
+                        join_table_child_col,
+                        child_table_id,
+                        child_column
+                    )
+                    .unwrap();
+                    self.write_laterals(&relation.projection, &nested);
+
+                    let param = self.add_param(limit);
+                    write!(
+                        &mut self.query,
+                        " WHERE T{}.{} = T{}.{} ORDER BY T{}.{} LIMIT ${}) AS S{}",
+                        join_table_id,
+                        join_table_parent_col,
+                        parent_table_id,
+                        parent_column,
+                        child_table_id,
+                        primary_key,
+                        param,
+                        child_table_id
+                    )
+                    .unwrap();
+
+                    self.pop_old_table();
+                }
+            }
+
+            write!(&mut self.query, ") AS L{} ON TRUE", lateral_id).unwrap();
+        }
+    }
+
+    fn write_from_where(&mut self, filter: &TableFilter<'v>) {
+        self.write_from(filter);
+        self.write_where(filter);
+    }
+
+    fn write_from(&mut self, filter: &TableFilter<'v>) {
+        let current_table_id = self.current_table_id();
+
         self.query.push_str(" FROM ");
         if let Some(j) = self.joined_table.as_ref() {
             write!(
@@ -76,13 +427,15 @@ impl<'v> SqlVisitor<'v> {
             )
             .unwrap();
         }
+    }
 
+    fn write_where(&mut self, filter: &TableFilter<'v>) {
         if !filter.filter_group().is_empty() || self.joined_table.is_some() {
             self.query.push_str(" WHERE ");
         }
 
         if !filter.filter_group().is_empty() {
-            filter.accept(&mut self);
+            filter.accept(self);
         }
 
         if let Some(j) = self.joined_table.take() {
@@ -91,15 +444,136 @@ impl<'v> SqlVisitor<'v> {
             }
 
             let param = self.add_param(j.join_table_filter_col_val);
-            write!(
-                &mut self.query,
-                "J.{} = ${}",
-                j.join_table_filter_col, param
-            )
+            match self.loader_key {
+                None => write!(
+                    &mut self.query,
+                    "J.{} = ${}",
+                    j.join_table_filter_col, param
+                ),
+                Some(_) => write!(
+                    &mut self.query,
+                    "J.{} = ANY (${})",
+                    j.join_table_filter_col, param
+                ),
+            }
             .unwrap();
         }
+    }
 
-        (self.query, self.params)
+    fn write_order_by(&mut self) {
+        let order = std::mem::take(&mut self.order);
+
+        for (idx, o) in order.iter().enumerate() {
+            if idx == 0 {
+                self.query.push_str(" ORDER BY ");
+            } else {
+                self.query.push_str(", ");
+            }
+
+            self.write_order_key(&o.key);
+            write!(&mut self.query, " {}", o.direction).unwrap();
+
+            if let Some(nulls) = o.nulls {
+                write!(&mut self.query, " {}", nulls).unwrap();
+            }
+        }
+    }
+
+    fn write_order_key(&mut self, key: &OrderKey<'v>) {
+        let parent_table_id = self.current_table_id();
+
+        match key {
+            OrderKey::Column(column) => {
+                write!(&mut self.query, "T{}.{}", parent_table_id, column).unwrap();
+            }
+            OrderKey::JoinColumn {
+                table,
+                parent_column,
+                child_column,
+                column,
+            } => {
+                let child_table_id = self.push_new_table();
+                write!(
+                    &mut self.query,
+                    "(SELECT T{}.{} FROM {} AS T{} WHERE T{}.{} = T{}.{})",
+                    child_table_id,
+                    column,
+                    table,
+                    child_table_id,
+                    child_table_id,
+                    child_column,
+                    parent_table_id,
+                    parent_column
+                )
+                .unwrap();
+                self.pop_old_table();
+            }
+            OrderKey::JoinTableCount {
+                join_table,
+                parent_table_join_col,
+                join_table_parent_col,
+            } => {
+                let join_table_id = self.push_new_table();
+                write!(
+                    &mut self.query,
+                    "(SELECT count(*) FROM {} AS T{} WHERE T{}.{} = T{}.{})",
+                    join_table,
+                    join_table_id,
+                    join_table_id,
+                    join_table_parent_col,
+                    parent_table_id,
+                    parent_table_join_col
+                )
+                .unwrap();
+                self.pop_old_table();
+            }
+            OrderKey::Rank => self.write_rank(),
+        }
+    }
+
+    fn write_rank(&mut self) {
+        let current_table_id = self.current_table_id();
+        let search = self.rank.expect("the rank requires a text search");
+        let param = self.add_param(search.query);
+        write!(
+            &mut self.query,
+            "ts_rank(T{}.{}, websearch_to_tsquery(${}))",
+            current_table_id, search.column, param
+        )
+        .unwrap();
+    }
+
+    fn write_page(&mut self) {
+        if let Some(p) = self.page.take() {
+            let param = self.add_param(p.limit);
+            write!(&mut self.query, " LIMIT ${}", param).unwrap();
+
+            if let Some(offset) = p.offset {
+                let param = self.add_param(offset);
+                write!(&mut self.query, " OFFSET ${}", param).unwrap();
+            }
+        }
+    }
+
+    fn write_partition_page(&mut self) {
+        let p = self.page.take().expect("the partition requires a page");
+        let limit = self.add_param(p.limit);
+
+        write!(&mut self.query, ") AS P WHERE P.{}", ROW_NUMBER_COLUMN).unwrap();
+        match p.offset {
+            None => write!(&mut self.query, " <= ${}", limit).unwrap(),
+            Some(offset) => {
+                let offset = self.add_param(offset);
+                write!(
+                    &mut self.query,
+                    " > ${} AND P.{} <= ${} + ${}",
+                    offset, ROW_NUMBER_COLUMN, offset, limit
+                )
+                .unwrap();
+            }
+        }
+
+        write!(&mut self.query, " ORDER BY P.{}", ROW_NUMBER_COLUMN).unwrap();
     }
 
     fn add_param(&mut self, p: &'v (dyn ToSql + Sync)) -> usize {
@@ -122,20 +596,37 @@ impl<'v> SqlVisitor<'v> {
     }
 }
 
+// Makes the LIKE metacharacters in the parameter match literally, so that
+// user input such as "50%_OFF" is not treated as a pattern
+fn escape_like(param: usize) -> String {
+    format!(
+        "replace(replace(replace(${}, '\\', '\\\\'), '%', '\\%'), '_', '\\_')",
+        param
+    )
+}
+
 impl<'v> Visitor<'v> for SqlVisitor<'v> {
     fn on_table_filter(&mut self, f: &TableFilter<'v>) {
         f.filter_group().accept(self);
     }
 
     fn on_filter_group(&mut self, f: &FilterGroup<'v>) {
-        let write_brackets = (f.filters().len() + f.groups().len()) > 1;
+        let negate = f.combinator() == Combinator::Not;
+        if negate {
+            self.query.push_str("NOT ");
+        }
+
+        // a single sub-group writes its own brackets
+        let members = f.filters().len() + f.groups().len();
+        let write_brackets = members > 1 || (negate && !f.filters().is_empty());
         if write_brackets {
             self.query.push_str("(");
         }
 
+        let combinator = f.combinator().joined_by();
         for (idx, filter) in f.filters().iter().enumerate() {
             if idx > 0 {
-                write!(&mut self.query, " {} ", f.combinator()).unwrap();
+                write!(&mut self.query, " {} ", combinator).unwrap();
             }
 
             filter.accept(self);
@@ -143,7 +634,7 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
 
         for (idx, group) in f.groups().iter().enumerate() {
             if idx > 0 || !f.filters().is_empty() {
-                write!(&mut self.query, " {} ", f.combinator()).unwrap();
+                write!(&mut self.query, " {} ", combinator).unwrap();
             }
 
             group.accept(self);
@@ -159,13 +650,28 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
             FilterType::ValueFilter(x) => x.accept(self),
             FilterType::JoinColumnFilter(x) => x.accept(self),
             FilterType::JoinTableFilter(x) => x.accept(self),
+            FilterType::JoinTableCountFilter(x) => x.accept(self),
         }
     }
 
     fn on_value_filter(&mut self, f: &ValueFilter<'v>) {
         let current_table_id = self.current_table_id();
 
-        write!(&mut self.query, "T{}.{} ", current_table_id, f.column()).unwrap();
+        write!(&mut self.query, "T{}.{}", current_table_id, f.column()).unwrap();
+
+        // pattern matching on `char(n)` columns would otherwise include the padding
+        if matches!(
+            f.operation(),
+            Operation::Contains
+                | Operation::IContains
+                | Operation::StartsWith
+                | Operation::EndsWith
+                | Operation::ILike
+                | Operation::Regex
+        ) {
+            self.query.push_str("::text");
+        }
+        self.query.push(' ');
 
         let val = f.value();
 
@@ -200,7 +706,7 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
             }
             Operation::NotIn => {
                 let param = self.add_param(val);
-                write!(&mut self.query, "<> ANY (${})", param)
+                write!(&mut self.query, "<> ALL (${})", param)
             }
             Operation::IsNull => {
                 write!(&mut self.query, "IS NULL")
@@ -210,7 +716,39 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
             }
             Operation::Contains => {
                 let param = self.add_param(val);
-                write!(&mut self.query, "LIKE '%' || ${} || '%'", param)
+                write!(&mut self.query, "LIKE '%' || {} || '%'", escape_like(param))
+            }
+            Operation::IContains => {
+                let param = self.add_param(val);
+                write!(
+                    &mut self.query,
+                    "ILIKE '%' || {} || '%'",
+                    escape_like(param)
+                )
+            }
+            Operation::StartsWith => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "LIKE {} || '%'", escape_like(param))
+            }
+            Operation::EndsWith => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "LIKE '%' || {}", escape_like(param))
+            }
+            Operation::ILike => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "ILIKE ${}", param)
+            }
+            Operation::Regex => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "~ ${}", param)
+            }
+            Operation::ArrayContains => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "@> ${}", param)
+            }
+            Operation::TextSearch => {
+                let param = self.add_param(val);
+                write!(&mut self.query, "@@ websearch_to_tsquery(${})", param)
             }
         }
         .unwrap();
@@ -246,6 +784,11 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
         let join_table_id = self.push_new_table();
         let child_table_id = self.push_new_table();
 
+        // every related row matches <=> there is no related row that does not match
+        if f.quantifier() != Quantifier::Some {
+            self.query.push_str("NOT ");
+        }
+
         write!(
             &mut self.query,
             "EXISTS(SELECT TRUE FROM {} AS T{} INNER JOIN {} AS T{} ON T{}.{} = T{}.{} WHERE T{}.{} = T{}.{}",
@@ -264,12 +807,684 @@ impl<'v> Visitor<'v> for SqlVisitor<'v> {
         ).unwrap();
 
         if !f.filter().filter_group().is_empty() {
-            self.query.push_str(" AND ");
-            f.filter().accept(self);
+            match f.quantifier() {
+                Quantifier::Every => {
+                    // a NULL result of the filter is not a match either
+                    self.query.push_str(" AND NOT COALESCE(");
+                    f.filter().accept(self);
+                    self.query.push_str(", FALSE)");
+                }
+                Quantifier::Some | Quantifier::None => {
+                    self.query.push_str(" AND ");
+                    f.filter().accept(self);
+                }
+            }
         }
 
         self.query.push_str(")");
         self.pop_old_table();
         self.pop_old_table();
     }
+
+    fn on_join_table_count_filter(&mut self, f: &JoinTableCountFilter<'v>) {
+        let j = f.join();
+        let parent_table_id = self.current_table_id();
+        let join_table_id = self.push_new_table();
+
+        write!(
+            &mut self.query,
+            "(SELECT count(*) FROM {} AS T{}",
+            j.join_table(),
+            join_table_id
+        )
+        .unwrap();
+
+        // the child table is needed only to filter the counted rows
+        let filtered = !j.filter().filter_group().is_empty();
+        if filtered {
+            let child_table_id = self.push_new_table();
+            write!(
+                &mut self.query,
+                " INNER JOIN {} AS T{} ON T{}.{} = T{}.{}",
+                j.filter().table_name(),
+                child_table_id,
+                child_table_id,
+                j.child_table_join_col(),
+                join_table_id,
+                j.join_table_child_col()
+            )
+            .unwrap();
+        }
+
+        write!(
+            &mut self.query,
+            " WHERE T{}.{} = T{}.{}",
+            join_table_id,
+            j.join_table_parent_col(),
+            parent_table_id,
+            j.parent_table_join_col()
+        )
+        .unwrap();
+
+        if filtered {
+            self.query.push_str(" AND ");
+            j.filter().accept(self);
+            self.pop_old_table();
+        }
+        self.pop_old_table();
+
+        let operator = match f.operation() {
+            Operation::Eq => "=",
+            Operation::Neq => "<>",
+            Operation::Lt => "<",
+            Operation::Gt => ">",
+            Operation::Lte => "<=",
+            Operation::Gte => ">=",
+            op => unreachable!("{:?} cannot be applied to a count", op),
+        };
+
+        let param = self.add_param(f.value());
+        write!(&mut self.query, ") {} ${}", operator, param).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::aggregate::{AggregateColumn, AggregateFunction};
+    use crate::query::order::{Direction, Nulls};
+    use crate::query::projection::ProjectedRelation;
+
+    fn translate(tf: &TableFilter<'_>) -> String {
+        SqlVisitor::new().translate(tf, &["actor_id"]).0
+    }
+
+    #[test]
+    fn not_in_must_differ_from_all_values() {
+        let names = vec!["A", "B"];
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::NotIn, "first_name", &names));
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE T0.first_name <> ALL ($1)"
+        );
+    }
+
+    #[test]
+    fn in_must_match_any_value() {
+        let names = vec!["A", "B"];
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::In, "first_name", &names));
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE T0.first_name = ANY ($1)"
+        );
+    }
+
+    #[test]
+    fn contains_escapes_like_metacharacters() {
+        let title = "50%_OFF";
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Contains, "title", &title));
+
+        assert_eq!(
+            SqlVisitor::new().translate(&tf, &["film_id"]).0,
+            r"SELECT T0.film_id FROM film AS T0 WHERE T0.title::text LIKE '%' || replace(replace(replace($1, '\', '\\'), '%', '\%'), '_', '\_') || '%'"
+        );
+    }
+
+    #[test]
+    fn string_operators() {
+        let value = "A";
+        let cases = [
+            (
+                Operation::IContains,
+                r"ILIKE '%' || replace(replace(replace($1, '\', '\\'), '%', '\%'), '_', '\_') || '%'",
+            ),
+            (
+                Operation::StartsWith,
+                r"LIKE replace(replace(replace($1, '\', '\\'), '%', '\%'), '_', '\_') || '%'",
+            ),
+            (
+                Operation::EndsWith,
+                r"LIKE '%' || replace(replace(replace($1, '\', '\\'), '%', '\%'), '_', '\_')",
+            ),
+            (Operation::ILike, "ILIKE $1"),
+            (Operation::Regex, "~ $1"),
+        ];
+
+        for (operation, expected) in cases {
+            let mut tf = TableFilter::new("actor");
+            tf.filter_group_mut()
+                .add_filter(ValueFilter::new(operation, "first_name", &value));
+
+            assert_eq!(
+                translate(&tf),
+                format!(
+                    "SELECT T0.actor_id FROM actor AS T0 WHERE T0.first_name::text {}",
+                    expected
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn text_search_ranked_by_relevance() {
+        let search = "drama -boat";
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut().add_filter(ValueFilter::new(
+            Operation::TextSearch,
+            "fulltext",
+            &search,
+        ));
+
+        let (sql, params) = SqlVisitor::new()
+            .rank(TextSearch {
+                column: "fulltext",
+                query: &search,
+            })
+            .order_by(vec![OrderBy {
+                key: OrderKey::Rank,
+                direction: Direction::Desc,
+                nulls: None,
+            }])
+            .translate(&tf, &["title"]);
+
+        assert_eq!(
+            sql,
+            "SELECT T0.title, ts_rank(T0.fulltext, websearch_to_tsquery($1)) AS __rank FROM film AS T0 \
+            WHERE T0.fulltext @@ websearch_to_tsquery($2) \
+            ORDER BY ts_rank(T0.fulltext, websearch_to_tsquery($3)) DESC"
+        );
+        assert_eq!(params.len(), 3);
+    }
+
+    #[test]
+    fn page_renders_limit_and_offset() {
+        let limit = 10i64;
+        let offset = 20i64;
+        let tf = TableFilter::new("film");
+
+        let (sql, params) = SqlVisitor::new()
+            .paginate(Page {
+                limit: &limit,
+                offset: None,
+            })
+            .translate(&tf, &["title"]);
+        assert_eq!(sql, "SELECT T0.title FROM film AS T0 LIMIT $1");
+        assert_eq!(params.len(), 1);
+
+        let (sql, params) = SqlVisitor::new()
+            .paginate(Page {
+                limit: &limit,
+                offset: Some(&offset),
+            })
+            .translate(&tf, &["title"]);
+        assert_eq!(sql, "SELECT T0.title FROM film AS T0 LIMIT $1 OFFSET $2");
+        assert_eq!(params.len(), 2);
+    }
+
+    #[test]
+    fn keyset_cursor_follows_the_primary_key() {
+        let after = 10;
+        let before = 20;
+        let limit = 6i64;
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Gt, "film_id", &after));
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Lt, "film_id", &before));
+
+        let (sql, params) = SqlVisitor::new()
+            .order_by(vec![OrderBy {
+                key: OrderKey::Column("film_id"),
+                direction: Direction::Desc,
+                nulls: None,
+            }])
+            .paginate(Page {
+                limit: &limit,
+                offset: None,
+            })
+            .translate(&tf, &["film_id", "title"]);
+
+        assert_eq!(
+            sql,
+            "SELECT T0.film_id, T0.title FROM film AS T0 \
+            WHERE (T0.film_id > $1 AND T0.film_id < $2) \
+            ORDER BY T0.film_id DESC LIMIT $3"
+        );
+        assert_eq!(params.len(), 3);
+    }
+
+    #[test]
+    fn order_by_columns_with_primary_key_tie_breaker() {
+        let tf = TableFilter::new("film");
+        let (sql, _) = SqlVisitor::new()
+            .order_by(vec![
+                OrderBy {
+                    key: OrderKey::Column("rating"),
+                    direction: Direction::Desc,
+                    nulls: Some(Nulls::Last),
+                },
+                OrderBy {
+                    key: OrderKey::Column("title"),
+                    direction: Direction::Asc,
+                    nulls: None,
+                },
+                OrderBy {
+                    key: OrderKey::Column("film_id"),
+                    direction: Direction::Asc,
+                    nulls: None,
+                },
+            ])
+            .translate(&tf, &["title"]);
+
+        assert_eq!(
+            sql,
+            "SELECT T0.title FROM film AS T0 \
+            ORDER BY T0.rating DESC NULLS LAST, T0.title ASC, T0.film_id ASC"
+        );
+    }
+
+    #[test]
+    fn order_by_related_column_and_relation_count() {
+        let length = 100;
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Gt, "length", &length));
+
+        let (sql, _) = SqlVisitor::new()
+            .order_by(vec![
+                OrderBy {
+                    key: OrderKey::JoinColumn {
+                        table: "language",
+                        parent_column: "language_id",
+                        child_column: "language_id",
+                        column: "name",
+                    },
+                    direction: Direction::Asc,
+                    nulls: Some(Nulls::First),
+                },
+                OrderBy {
+                    key: OrderKey::JoinTableCount {
+                        join_table: "film_actor",
+                        parent_table_join_col: "film_id",
+                        join_table_parent_col: "film_id",
+                    },
+                    direction: Direction::Desc,
+                    nulls: None,
+                },
+                OrderBy {
+                    key: OrderKey::Column("film_id"),
+                    direction: Direction::Asc,
+                    nulls: None,
+                },
+            ])
+            .translate(&tf, &["title"]);
+
+        assert_eq!(
+            sql,
+            "SELECT T0.title FROM film AS T0 WHERE T0.length > $1 ORDER BY \
+            (SELECT T1.name FROM language AS T1 WHERE T1.language_id = T0.language_id) ASC NULLS FIRST, \
+            (SELECT count(*) FROM film_actor AS T2 WHERE T2.film_id = T0.film_id) DESC, \
+            T0.film_id ASC"
+        );
+    }
+
+    fn film_actors(quantifier: Quantifier, name: &str) -> String {
+        let name = name.to_owned();
+        let mut actors = TableFilter::new("actor");
+        actors
+            .filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Eq, "first_name", &name));
+
+        let jf = JoinTableFilter::new(
+            actors,
+            "film_actor",
+            "film_id",
+            "film_id",
+            "actor_id",
+            "actor_id",
+        )
+        .quantified(quantifier);
+
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut().add_filter(jf);
+
+        SqlVisitor::new().translate(&tf, &["title"]).0
+    }
+
+    #[test]
+    fn some_related_rows_match() {
+        assert_eq!(
+            film_actors(Quantifier::Some, "A"),
+            "SELECT T0.title FROM film AS T0 WHERE EXISTS(SELECT TRUE FROM film_actor AS T1 \
+            INNER JOIN actor AS T2 ON T2.actor_id = T1.actor_id \
+            WHERE T1.film_id = T0.film_id AND T2.first_name = $1)"
+        );
+    }
+
+    #[test]
+    fn every_related_row_matches() {
+        assert_eq!(
+            film_actors(Quantifier::Every, "A"),
+            "SELECT T0.title FROM film AS T0 WHERE NOT EXISTS(SELECT TRUE FROM film_actor AS T1 \
+            INNER JOIN actor AS T2 ON T2.actor_id = T1.actor_id \
+            WHERE T1.film_id = T0.film_id AND NOT COALESCE(T2.first_name = $1, FALSE))"
+        );
+    }
+
+    #[test]
+    fn no_related_row_matches() {
+        assert_eq!(
+            film_actors(Quantifier::None, "A"),
+            "SELECT T0.title FROM film AS T0 WHERE NOT EXISTS(SELECT TRUE FROM film_actor AS T1 \
+            INNER JOIN actor AS T2 ON T2.actor_id = T1.actor_id \
+            WHERE T1.film_id = T0.film_id AND T2.first_name = $1)"
+        );
+    }
+
+    #[test]
+    fn count_of_related_rows() {
+        let count = 10i64;
+        let join = JoinTableFilter::new(
+            TableFilter::new("actor"),
+            "film_actor",
+            "film_id",
+            "film_id",
+            "actor_id",
+            "actor_id",
+        );
+
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(JoinTableCountFilter::new(join, Operation::Gte, &count));
+
+        assert_eq!(
+            SqlVisitor::new().translate(&tf, &["title"]).0,
+            "SELECT T0.title FROM film AS T0 WHERE (SELECT count(*) FROM film_actor AS T1 \
+            WHERE T1.film_id = T0.film_id) >= $1"
+        );
+    }
+
+    #[test]
+    fn count_of_filtered_related_rows() {
+        let name = "A";
+        let count = 5i64;
+        let mut actors = TableFilter::new("actor");
+        actors
+            .filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Eq, "first_name", &name));
+
+        let join = JoinTableFilter::new(
+            actors,
+            "film_actor",
+            "film_id",
+            "film_id",
+            "actor_id",
+            "actor_id",
+        );
+
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(JoinTableCountFilter::new(join, Operation::Lt, &count));
+
+        assert_eq!(
+            SqlVisitor::new().translate(&tf, &["title"]).0,
+            "SELECT T0.title FROM film AS T0 WHERE (SELECT count(*) FROM film_actor AS T1 \
+            INNER JOIN actor AS T2 ON T2.actor_id = T1.actor_id \
+            WHERE T1.film_id = T0.film_id AND T2.first_name = $1) < $2"
+        );
+    }
+
+    #[test]
+    fn not_group_with_single_filter() {
+        let name = "A";
+        let mut not = FilterGroup::new(Combinator::Not);
+        not.add_filter(ValueFilter::new(Operation::Eq, "first_name", &name));
+
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut().add_group(not);
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE NOT (T0.first_name = $1)"
+        );
+    }
+
+    #[test]
+    fn not_group_is_a_negated_conjunction() {
+        let first_name = "A";
+        let last_name = "B";
+        let mut not = FilterGroup::new(Combinator::Not);
+        not.add_filter(ValueFilter::new(Operation::Eq, "first_name", &first_name));
+        not.add_filter(ValueFilter::new(Operation::Eq, "last_name", &last_name));
+
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut().add_group(not);
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE NOT (T0.first_name = $1 AND T0.last_name = $2)"
+        );
+    }
+
+    #[test]
+    fn not_group_is_not_merged_into_parent() {
+        let first_name = "A";
+        let last_name = "B";
+        let mut not = FilterGroup::new(Combinator::Not);
+        not.add_filter(ValueFilter::new(Operation::Eq, "first_name", &first_name));
+
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Eq, "last_name", &last_name));
+        tf.filter_group_mut().add_group(not);
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE (T0.last_name = $1 AND NOT (T0.first_name = $2))"
+        );
+    }
+
+    #[test]
+    fn nested_not_groups_are_preserved() {
+        let first_name = "A";
+        let last_name = "B";
+
+        let mut inner = FilterGroup::new(Combinator::Not);
+        inner.add_filter(ValueFilter::new(Operation::Eq, "last_name", &last_name));
+
+        let mut outer = FilterGroup::new(Combinator::Not);
+        outer.add_filter(ValueFilter::new(Operation::Eq, "first_name", &first_name));
+        outer.add_group(inner);
+
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut().add_group(outer);
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE NOT (T0.first_name = $1 AND NOT (T0.last_name = $2))"
+        );
+    }
+
+    #[test]
+    fn or_group_inside_not_group() {
+        let first_name = "A";
+        let last_name = "B";
+
+        let mut or = FilterGroup::new(Combinator::Or);
+        or.add_filter(ValueFilter::new(Operation::Eq, "first_name", &first_name));
+        or.add_filter(ValueFilter::new(Operation::Eq, "last_name", &last_name));
+
+        let mut not = FilterGroup::new(Combinator::Not);
+        not.add_group(or);
+
+        let mut tf = TableFilter::new("actor");
+        tf.filter_group_mut().add_group(not);
+
+        assert_eq!(
+            translate(&tf),
+            "SELECT T0.actor_id FROM actor AS T0 WHERE NOT (T0.first_name = $1 OR T0.last_name = $2)"
+        );
+    }
+
+    #[test]
+    fn aggregate_groups_by_column_and_join_table() {
+        let length = 100;
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Gt, "length", &length));
+
+        let aggregate = Aggregate {
+            group_by: vec![
+                GroupKey::Column("rating"),
+                GroupKey::JoinTable {
+                    join_table: "film_category",
+                    parent_table_join_col: "film_id",
+                    join_table_parent_col: "film_id",
+                    column: "category_id",
+                },
+            ],
+            columns: vec![AggregateColumn {
+                function: AggregateFunction::Avg,
+                column: "length",
+            }],
+        };
+
+        assert_eq!(
+            SqlVisitor::new().translate_aggregate(&tf, &aggregate).0,
+            "SELECT count(*) AS count, T0.rating, T1.category_id, avg(T0.length)::numeric AS avg_length \
+            FROM film AS T0 INNER JOIN film_category AS T1 ON T1.film_id = T0.film_id \
+            WHERE T0.length > $1 GROUP BY T0.rating, T1.category_id ORDER BY T0.rating, T1.category_id"
+        );
+    }
+
+    #[test]
+    fn loader_key_matches_any_parent() {
+        let ids = vec![1, 2];
+        let joined_table = JoinedTable {
+            join_table: "film_actor",
+            join_table_join_col: "film_id",
+            data_table_join_col: "film_id",
+            join_table_filter_col: "actor_id",
+            join_table_filter_col_val: &ids,
+        };
+
+        let tf = TableFilter::new("film");
+        let (sql, _) = SqlVisitor::with_joined_table(joined_table)
+            .loader_key("__loader_key")
+            .translate(&tf, &["title"]);
+
+        assert_eq!(
+            sql,
+            "SELECT T0.title, J.actor_id AS __loader_key FROM film_actor AS J \
+            INNER JOIN film AS T0 ON J.film_id = T0.film_id WHERE J.actor_id = ANY ($1)"
+        );
+    }
+
+    #[test]
+    fn loader_key_pages_the_filtered_rows_of_every_parent() {
+        let ids = vec![1, 2];
+        let length = 100;
+        let limit = 5i64;
+        let offset = 10i64;
+        let joined_table = JoinedTable {
+            join_table: "film_actor",
+            join_table_join_col: "film_id",
+            data_table_join_col: "film_id",
+            join_table_filter_col: "actor_id",
+            join_table_filter_col_val: &ids,
+        };
+
+        let mut tf = TableFilter::new("film");
+        tf.filter_group_mut()
+            .add_filter(ValueFilter::new(Operation::Gt, "length", &length));
+
+        let (sql, params) = SqlVisitor::with_joined_table(joined_table)
+            .loader_key("__loader_key")
+            .order_by(vec![
+                OrderBy {
+                    key: OrderKey::Column("title"),
+                    direction: Direction::Desc,
+                    nulls: None,
+                },
+                OrderBy {
+                    key: OrderKey::Column("film_id"),
+                    direction: Direction::Asc,
+                    nulls: None,
+                },
+            ])
+            .paginate(Page {
+                limit: &limit,
+                offset: Some(&offset),
+            })
+            .translate(&tf, &[]);
+
+        assert_eq!(
+            sql,
+            "SELECT * FROM (SELECT T0.*, J.actor_id AS __loader_key, \
+            ROW_NUMBER() OVER (PARTITION BY J.actor_id ORDER BY T0.title DESC, T0.film_id ASC) AS __row_number \
+            FROM film_actor AS J INNER JOIN film AS T0 ON J.film_id = T0.film_id \
+            WHERE T0.length > $1 AND J.actor_id = ANY ($2)) AS P \
+            WHERE P.__row_number > $4 AND P.__row_number <= $4 + $3 ORDER BY P.__row_number"
+        );
+        assert_eq!(params.len(), 4);
+    }
+
+    #[test]
+    fn projection_nests_relations_as_lateral_subqueries() {
+        let limit = 10i64;
+        let projection = Projection {
+            columns: vec!["title"],
+            relations: vec![
+                ProjectedRelation {
+                    key: "language",
+                    table: "language",
+                    join: RelationJoin::ToOne {
+                        parent_column: "language_id",
+                        child_column: "language_id",
+                    },
+                    projection: Projection {
+                        columns: vec!["name"],
+                        relations: vec![],
+                    },
+                },
+                ProjectedRelation {
+                    key: "actors",
+                    table: "actor",
+                    join: RelationJoin::ToMany {
+                        parent_column: "film_id",
+                        join_table: "film_actor",
+                        join_table_parent_col: "film_id",
+                        join_table_child_col: "actor_id",
+                        child_column: "actor_id",
+                        primary_key: "actor_id",
+                        limit: &limit,
+                    },
+                    projection: Projection {
+                        columns: vec!["first_name"],
+                        relations: vec![],
+                    },
+                },
+            ],
+        };
+
+        let tf = TableFilter::new("film");
+        let (sql, _) = SqlVisitor::new().translate_projection(&tf, &projection, "__value");
+
+        assert_eq!(
+            sql,
+            "SELECT json_build_object('title', T0.title, '__prefetched', json_build_object('language', L1.value, 'actors', L2.value)) AS __value \
+            FROM film AS T0 \
+            LEFT JOIN LATERAL (SELECT json_build_object('name', T3.name) AS value FROM language AS T3 WHERE T3.language_id = T0.language_id) AS L1 ON TRUE \
+            LEFT JOIN LATERAL (SELECT coalesce(json_agg(S5.value ORDER BY S5.key), '[]') AS value FROM (SELECT T5.actor_id AS key, json_build_object('first_name', T5.first_name) AS value \
+            FROM film_actor AS T4 INNER JOIN actor AS T5 ON T4.actor_id = T5.actor_id WHERE T4.film_id = T0.film_id ORDER BY T5.actor_id LIMIT $1) AS S5) AS L2 ON TRUE"
+        );
+    }
 }
@@ -0,0 +1,10 @@
+use tokio_postgres::types::ToSql;
+
+// The column name, under which the rank of a row is returned
+pub const RANK_COLUMN: &str = "__rank";
+
+#[derive(Debug, Copy, Clone)]
+pub struct TextSearch<'f> {
+    pub column: &'f str,
+    pub query: &'f (dyn ToSql + Sync),
+}
@@ -1,6 +1,7 @@
 use crate::query::filter_group::FilterGroup;
 use crate::query::filter_type::FilterType;
 use crate::query::join_column_filter::JoinColumnFilter;
+use crate::query::join_table_count_filter::JoinTableCountFilter;
 use crate::query::join_table_filter::JoinTableFilter;
 use crate::query::table_filter::TableFilter;
 use crate::query::value_filter::ValueFilter;
@@ -12,4 +13,5 @@ pub trait Visitor<'f> {
     fn on_table_filter(&mut self, f: &TableFilter<'f>);
     fn on_join_column_filter(&mut self, f: &JoinColumnFilter<'f>);
     fn on_join_table_filter(&mut self, f: &JoinTableFilter<'f>);
+    fn on_join_table_count_filter(&mut self, f: &JoinTableCountFilter<'f>);
 }
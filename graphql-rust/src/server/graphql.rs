@@ -26,8 +26,10 @@ use tower_service::Service;
 
 use crate::config::DataLoaderConfig;
 use crate::graphql::loader::{
-    ActorFilmIdLoader, ActorLoader, CategoryLoader, FilmActorIdLoader, FilmCategoryIdLoader,
-    FilmLoader, LanguageLoader,
+    ActorFilmCountLoader, ActorFilmFilteredLoader, ActorFilmIdLoader, ActorLoader, AddressLoader,
+    CategoryLoader, CityLoader, CountryLoader, CustomerRentalLoader, FilmActorCountLoader,
+    FilmActorFilteredLoader, FilmActorIdLoader, FilmCategoryIdLoader, FilmInventoryLoader,
+    FilmLoader, InventoryLoader, LanguageLoader, RentalPaymentLoader, StaffLoader, StoreLoader,
 };
 use crate::server::{AppState, Database};
 
@@ -175,6 +177,46 @@ fn attach_data_loaders<R: WithData>(r: &mut R, cfg: &DataLoaderConfig, db: &Data
         .delay(Duration::from_millis(cfg.default_delay_ms))
     });
 
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            ActorFilmCountLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            ActorFilmFilteredLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            FilmActorFilteredLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            FilmActorCountLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
     r.insert_with(|| {
         DataLoader::with_cache(
             FilmLoader::new(db.db.clone()),
@@ -204,4 +246,94 @@ fn attach_data_loaders<R: WithData>(r: &mut R, cfg: &DataLoaderConfig, db: &Data
         .max_batch_size(cfg.max_batch_size)
         .delay(Duration::from_millis(cfg.default_delay_ms))
     });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            AddressLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            CityLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            CountryLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            StoreLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            StaffLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            FilmInventoryLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            InventoryLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            CustomerRentalLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
+
+    r.insert_with(|| {
+        DataLoader::with_cache(
+            RentalPaymentLoader::new(db.db.clone()),
+            tokio::task::spawn,
+            HashMapCache::<FxBuildHasher>::new(),
+        )
+        .max_batch_size(cfg.max_batch_size)
+        .delay(Duration::from_millis(cfg.default_delay_ms))
+    });
 }
@@ -6,6 +6,7 @@ use std::error::Error;
 use std::time::Duration;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::{DefaultOnResponse, TraceLayer};
 use tower_http::ServiceBuilderExt;
@@ -45,7 +46,9 @@ pub async fn start_server(
         .propagate_x_request_id()
         .layer(timeout_layer);
 
-    let gql_service = GraphQL::new(config.data_loader, state.clone(), schema);
+    let gql_service = ServiceBuilder::new()
+        .layer(RequestBodyLimitLayer::new(config.server.max_body_size))
+        .service(GraphQL::new(config.data_loader, state.clone(), schema));
 
     let router = Router::new()
         .route(PLAYGROUND_ENDPOINT, get(playground))